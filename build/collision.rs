@@ -0,0 +1,138 @@
+use super::schema::TiledSpec;
+
+/// A rectangle of tiles, measured in tiles rather than pixels.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Merges the solid tiles of a grid into as few rectangles as it can find (greedily), so that each
+/// becomes one `Wall` instead of one per tile.
+///
+/// Starting from each solid tile that is not yet covered, in reading order, the rectangle is first
+/// extended right as far as the row allows, then down for as long as every tile of the next row
+/// beneath it is also solid and uncovered.
+pub fn merge_tiles(solid: &[bool], width: u32) -> Vec<TileRect> {
+    let width = width as usize;
+    let height = solid.len() / width;
+    let mut covered = vec![false; solid.len()];
+    let available = |covered: &[bool], x: usize, y: usize| solid[y * width + x] && !covered[y * width + x];
+
+    let mut rects = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            if !available(&covered, x, y) {
+                continue;
+            }
+            let mut w = 1;
+            while x + w < width && available(&covered, x + w, y) {
+                w += 1;
+            }
+            let mut h = 1;
+            while y + h < height && (x..x + w).all(|x| available(&covered, x, y + h)) {
+                h += 1;
+            }
+            for yy in y..y + h {
+                for xx in x..x + w {
+                    covered[yy * width + xx] = true;
+                }
+            }
+            rects.push(TileRect {
+                x: x as u32,
+                y: y as u32,
+                width: w as u32,
+                height: h as u32,
+            });
+        }
+    }
+    rects
+}
+
+/// Which tiles of a map are solid. A tile is solid if it is set in the `COLLISIONS` layer, or if
+/// any layer has a tile whose tile set gives it the `solid` property.
+pub fn solid_tiles(map: &TiledSpec) -> Vec<bool> {
+    let mut solid = vec![false; (map.width * map.height) as usize];
+    for layer in &map.layers {
+        let is_collisions = layer.name.to_uppercase() == "COLLISIONS";
+        for (index, tile) in layer.tiles.iter().enumerate() {
+            if tile.gid == 0 {
+                continue;
+            }
+            solid[index] |= is_collisions || map
+                .tilesets
+                .iter()
+                .find(|set| set.contains(tile.gid))
+                .map(|set| set.is_solid(tile.gid - set.firstgid))
+                .unwrap_or(false);
+        }
+    }
+    solid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> (Vec<bool>, u32) {
+        let solid = rows.iter().flat_map(|row| row.chars()).map(|tile| tile == '#').collect();
+        (solid, rows[0].len() as u32)
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> TileRect {
+        TileRect { x, y, width, height }
+    }
+
+    /// How many of the rectangles cover each tile.
+    fn coverage(rects: &[TileRect], solid: &[bool], width: u32) -> Vec<u32> {
+        let mut coverage = vec![0; solid.len()];
+        for rect in rects {
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    coverage[(y * width + x) as usize] += 1;
+                }
+            }
+        }
+        coverage
+    }
+
+    #[test]
+    fn rows_of_different_lengths() {
+        let (solid, width) = grid(&["###.", "##.."]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 3, 1), rect(0, 1, 2, 1)]);
+        let (solid, width) = grid(&["##..", "###."]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 2, 2), rect(2, 1, 1, 1)]);
+    }
+
+    #[test]
+    fn l_shape() {
+        let (solid, width) = grid(&["#..", "#..", "###"]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 1, 3), rect(1, 2, 2, 1)]);
+        let (solid, width) = grid(&["###", "..#", "..#"]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 3, 1), rect(2, 1, 1, 2)]);
+    }
+
+    #[test]
+    fn single_column() {
+        let (solid, width) = grid(&["#", "#", "#"]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 1, 3)]);
+        let (solid, width) = grid(&["#", ".", "#"]);
+        assert_eq!(merge_tiles(&solid, width), vec![rect(0, 0, 1, 1), rect(0, 2, 1, 1)]);
+    }
+
+    #[test]
+    fn no_tile_covered_twice() {
+        for rows in &[
+            &[".#.", "###", ".#."][..],
+            &["##.", "###", ".##"][..],
+            &["#.#", "###", "#.#"][..],
+        ] {
+            let (solid, width) = grid(rows);
+            let rects = merge_tiles(&solid, width);
+            let expected: Vec<_> = solid.iter().map(|solid| *solid as u32).collect();
+            assert_eq!(coverage(&rects, &solid, width), expected, "{:?}", rows);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::de::DeserializeOwned;
+use serde_xml_rs::deserialize;
+
+/// A problem with one of the asset files, naming the file, the field within it, and what is wrong
+/// with the value that was found there.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub field: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(file: &Path, field: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            file: file.to_owned(),
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.file.display(), self.message)
+        } else {
+            write!(f, "{}: `{}`: {}", self.file.display(), self.field, self.message)
+        }
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Diagnostic>;
+
+/// Parses a number from one of the string attributes of an XML file.
+pub fn parse_number<T: FromStr>(file: &Path, field: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| Diagnostic::new(file, field, format!("expected a number, but found {:?}", value)))
+}
+
+pub fn read_toml<T: DeserializeOwned>(file: &Path) -> Result<T> {
+    let toml_str = fs::read_to_string(file)
+        .map_err(|error| Diagnostic::new(file, "", format!("could not be read: {}", error)))?;
+    toml::from_str(&toml_str)
+        .map_err(|error| Diagnostic::new(file, "", format!("is not valid: {}", error)))
+}
+
+/// Parses XML that was read from the `file`, for when it has to be changed before it is parsed.
+pub fn parse_xml<T: DeserializeOwned>(file: &Path, xml: &str) -> Result<T> {
+    deserialize(xml.as_bytes())
+        .map_err(|error| Diagnostic::new(file, "", format!("is not valid: {}", error)))
+}
+
+pub fn read_xml<T: DeserializeOwned>(file: &Path) -> Result<T> {
+    let xml_file = File::open(file)
+        .map_err(|error| Diagnostic::new(file, "", format!("could not be read: {}", error)))?;
+    deserialize(xml_file)
+        .map_err(|error| Diagnostic::new(file, "", format!("is not valid: {}", error)))
+}
@@ -1,5 +1,4 @@
 use std::{
-    path::PathBuf,
     fs::{self, ReadDir},
     io::Write,
     ffi::OsStr,
@@ -9,7 +8,10 @@ use toml::from_str;
 
 use super::schema::*;
 
+/// Writes a module of `Font` constants for each font TOML, along with the table of all of them by
+/// family, style, and size, which is used to find the font for a text style rule.
 pub fn write_fonts<'a, W: Write>(file: &mut W, paths: ReadDir) {
+    let mut table = vec![];
     for path in paths {
         let path = path.unwrap().path();
         if !path.is_dir() && path.extension() == Some(&OsStr::new("toml")) {
@@ -19,14 +21,15 @@ pub fn write_fonts<'a, W: Write>(file: &mut W, paths: ReadDir) {
             let toml_str = fs::read_to_string(&path).unwrap();
             let fonts: FontSpec = from_str(&toml_str).unwrap();
             for font in fonts.styles {
-                let mut file_path = PathBuf::from("src/font");
-                file_path.push(font.file);
+                let file_path = path.parent().unwrap().join(font.file);
                 for size in font.sizes {
                     let const_name = format!("{}_{}", font.name.to_uppercase(), size);
                     writeln!(file, "pub const {}: Font = Font::new({:?}, {});", const_name, file_path, size).unwrap();
+                    table.push(format!("({:?}, {:?}, {}, &{}::{})", name.to_str().unwrap(), font.name, size, name.to_str().unwrap(), const_name));
                 }
             }
             writeln!(file, "}}").unwrap();
         }
     }
+    writeln!(file, "pub const FONTS: &[(&str, &str, u32, &Font)] = &[{}];", table.join(", ")).unwrap();
 }
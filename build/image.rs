@@ -4,6 +4,8 @@ use std::{
     ffi::OsStr,
 };
 
+/// Writes an `Image` constant for each image, which the engine loads from the image's absolute
+/// path, so that the game can be run from any directory.
 pub fn write_images<'a, W: Write>(file: &mut W, paths: ReadDir) {
     for path in paths {
         let path = path.unwrap().path();
@@ -20,3 +22,29 @@ pub fn write_images<'a, W: Write>(file: &mut W, paths: ReadDir) {
         }
     }
 }
+
+/// Generates a function that finds an image by the name that the sprites give it (e.g. `sub::IMAGE`
+/// for `image/sub/image.png`), which is used to look up the image of a sprite while hot reloading.
+pub fn write_image_names<W: Write>(file: &mut W, paths: ReadDir) {
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "pub fn named(name: &str) -> Option<Image> {{").unwrap();
+    writeln!(file, "match name {{").unwrap();
+    write_image_name_arms(file, paths, "");
+    writeln!(file, "_ => None,").unwrap();
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "}}").unwrap();
+}
+
+fn write_image_name_arms<W: Write>(file: &mut W, paths: ReadDir, prefix: &str) {
+    for path in paths {
+        let path = path.unwrap().path();
+        if path.is_dir() {
+            let module = path.file_name().unwrap().to_str().unwrap().to_owned().to_lowercase();
+            let sub_paths = fs::read_dir(&path).unwrap();
+            write_image_name_arms(file, sub_paths, &format!("{}{}::", prefix, module));
+        } else if path.extension() != Some(&OsStr::new("rs")) {
+            let const_name = path.file_stem().unwrap().to_str().unwrap().to_owned().to_uppercase();
+            writeln!(file, "\"{}{}\" => Some({}{}),", prefix, const_name, prefix, const_name).unwrap();
+        }
+    }
+}
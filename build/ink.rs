@@ -2,6 +2,14 @@ use inkgen::{parse, pretty_print};
 use std::io::Write;
 use std::ffi::OsStr;
 use std::fs::{read_to_string, read_dir, ReadDir};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::diagnostic::Diagnostic;
+use super::markup::{split_speaker, split_expression, tokenize, Token, MarkupError};
+use super::command::{self, Command};
+use super::condition::{Expression, FUNCTIONS};
+use super::item::Item;
 
 pub fn write_inks<'a, W: Write>(file: &mut W, paths: ReadDir) {
     for path in paths {
@@ -24,3 +32,173 @@ pub fn write_inks<'a, W: Write>(file: &mut W, paths: ReadDir) {
         }
     }
 }
+
+/// Checks the markup of every line and choice of an ink story, so that any mistake is found now
+/// rather than when the line is shown. An expression may only be given to a speaker that has it
+/// in the speaker registry, conditions may only call the game's functions, and the commands in
+/// the tags must be written correctly.
+pub fn lint_ink(
+    path: &Path,
+    ink: &str,
+    rules: &HashSet<String>,
+    speakers: &HashMap<String, HashSet<String>>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for (line_number, line) in ink_lines(ink) {
+        let field = format!("line {}", line_number);
+        let (speaker, text) = split_speaker(&line);
+        if speaker == Some("") {
+            diagnostics.push(Diagnostic::new(path, field.clone(), "the line starts with a : but does not name a speaker"));
+        }
+        if let Some((speaker, Some(expression))) = speaker.map(split_expression) {
+            match speakers.get(speaker) {
+                None => diagnostics.push(Diagnostic::new(
+                    path,
+                    field.clone(),
+                    format!("{} has an expression, but is not in the speaker registry", speaker),
+                )),
+                Some(expressions) if !expressions.contains(expression) => diagnostics.push(Diagnostic::new(
+                    path,
+                    field.clone(),
+                    format!("{} has no {:?} expression", speaker, expression),
+                )),
+                Some(..) => {}
+            }
+        }
+        match tokenize(text) {
+            Err(error) => diagnostics.push(Diagnostic::new(path, field, error.to_string())),
+            Ok(tokens) => {
+                for token in tokens {
+                    match token {
+                        Token::Open(rule, column) => {
+                            if !rules.contains(&rule) {
+                                diagnostics.push(Diagnostic::new(path, field.clone(), MarkupError::UnknownRule { rule, column }.to_string()));
+                            }
+                        }
+                        Token::Condition(condition) => diagnostics.extend(lint_calls(path, &field, &condition)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    for (line_number, tag) in ink_tags(ink) {
+        let field = format!("line {}", line_number);
+        match command::parse(&tag) {
+            Some(Err(error)) => diagnostics.push(Diagnostic::new(path, field, error.to_string())),
+            Some(Ok(Command::Set(_, value))) => diagnostics.extend(lint_calls(path, &field, &value)),
+            | Some(Ok(Command::Give(item, _)))
+            | Some(Ok(Command::Take(item, _))) => {
+                if Item::find(&item).is_none() {
+                    diagnostics.push(Diagnostic::new(path, field, format!("there is no item named {:?}", item)));
+                }
+            }
+            Some(Ok(Command::Bark(_, text))) => match tokenize(&text) {
+                Err(error) => diagnostics.push(Diagnostic::new(path, field, error.to_string())),
+                Ok(tokens) => {
+                    for token in tokens {
+                        match token {
+                            Token::Open(rule, column) => {
+                                if !rules.contains(&rule) {
+                                    diagnostics.push(Diagnostic::new(path, field.clone(), MarkupError::UnknownRule { rule, column }.to_string()));
+                                }
+                            }
+                            Token::Condition(..) => diagnostics.push(Diagnostic::new(path, field.clone(), "a bark cannot have a condition")),
+                            _ => {}
+                        }
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+/// Checks that an expression only calls the functions that the game provides, with the right
+/// number of arguments.
+fn lint_calls(path: &Path, field: &str, expression: &Expression) -> Vec<Diagnostic> {
+    expression.calls()
+        .into_iter()
+        .filter_map(|(function, count)| match FUNCTIONS.iter().find(|(name, _)| *name == function) {
+            None => Some(Diagnostic::new(path, field.to_owned(), format!("there is no function named {:?}", function))),
+            Some((_, arguments)) if *arguments != count => Some(Diagnostic::new(
+                path,
+                field.to_owned(),
+                format!("{} takes {} arguments, but is given {}", function, arguments, count),
+            )),
+            Some(..) => None,
+        })
+        .collect()
+}
+
+/// The tags of an ink story, along with the line that each is on.
+fn ink_tags(ink: &str) -> Vec<(usize, String)> {
+    let mut tags = vec![];
+    for (i, line) in ink.lines().enumerate() {
+        let mut line = line.trim();
+        if let Some(comment) = line.find("//") {
+            line = &line[..comment];
+        }
+        for tag in line.split('#').skip(1) {
+            tags.push((i + 1, tag.trim().to_owned()));
+        }
+    }
+    tags
+}
+
+/// The text of each line and choice of an ink story, as it will be shown, along with the line it
+/// starts on. This only understands as much ink as the dialogs use: knots, stitches, logic, tags,
+/// diverts, comments, choices and gathers are removed, and lines joined by glue are put back
+/// together.
+fn ink_lines(ink: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    let mut glued = false;
+    for (i, line) in ink.lines().enumerate() {
+        let mut line = line.trim();
+        if let Some(comment) = line.find("//") {
+            line = line[..comment].trim();
+        }
+        if line.is_empty()
+            || line.starts_with('=')
+            || line.starts_with('~')
+            || line.starts_with("VAR ")
+            || line.starts_with("CONST ")
+            || line.starts_with("INCLUDE ")
+            || line.starts_with("TODO") {
+            continue;
+        }
+        // choices and gathers
+        let is_choice = line.starts_with('*') || line.starts_with('+');
+        line = line.trim_start_matches(|ch| ch == '*' || ch == '+' || ch == '-' || ch == ' ');
+        if line.starts_with('(') && line.contains(')') && !line.contains("):") {
+            // a label, like `* (name) text`
+            line = line[line.find(')').unwrap() + 1..].trim();
+        }
+        if let Some(tag) = line.find('#') {
+            line = line[..tag].trim();
+        }
+        if let Some(divert) = line.find("->") {
+            line = line[..divert].trim();
+        }
+        // only the brackets of a choice are ink, elsewhere they are text (e.g. `You[worried]:`)
+        let text: String = if is_choice {
+            line.chars().filter(|&ch| ch != '[' && ch != ']').collect()
+        } else {
+            line.to_owned()
+        };
+        let (text, glue) = if text.ends_with("<>") {
+            (text[..text.len() - 2].to_owned(), true)
+        } else {
+            (text, false)
+        };
+        let text = text.replace("<>", "");
+        if glued {
+            lines.last_mut().unwrap().1.push_str(&text);
+        } else if !text.is_empty() {
+            lines.push((i + 1, text));
+        }
+        glued = glue && !lines.is_empty();
+    }
+    lines
+}
@@ -5,6 +5,8 @@ extern crate toml;
 extern crate serde;
 #[macro_use] extern crate serde_derive;
 extern crate serde_xml_rs;
+extern crate base64;
+extern crate flate2;
 
 use std::{
     env,
@@ -12,9 +14,22 @@ use std::{
     fs::{self, File},
 };
 
+mod diagnostic;
+#[path = "../src/model/command.rs"]
+mod command;
+#[path = "../src/model/condition.rs"]
+mod condition;
+#[path = "../src/model/item.rs"]
+mod item;
+#[path = "../src/model/markup.rs"]
+mod markup;
 mod schema;
+mod validate;
+mod collision;
 mod image;
 mod ink;
+mod rule;
+mod speaker;
 mod sprite;
 mod font;
 mod tile_set;
@@ -23,10 +38,13 @@ mod tile_grid;
 use self::{
     image::*,
     ink::*,
+    rule::*,
+    speaker::*,
     sprite::*,
     font::*,
     tile_set::*,
     tile_grid::*,
+    validate::validate,
 };
 
 // Generates the images, sprites, and fonts modules
@@ -35,32 +53,50 @@ fn main() {
     resources_dir.push("src");
     let dest_path = PathBuf::from(env::var("OUT_DIR").unwrap());
 
+    let diagnostics = validate(&resources_dir);
+    if !diagnostics.is_empty() {
+        for diagnostic in &diagnostics {
+            eprintln!("error: {}", diagnostic);
+        }
+        panic!("Found {} problems with the assets", diagnostics.len());
+    }
+
     let images_dir = resources_dir.join("image");
     let images_out_path = dest_path.join("images.rs");
     let mut images_out_file = File::create(images_out_path).unwrap();
-    write_images(&mut images_out_file, fs::read_dir(images_dir).unwrap());
+    write_images(&mut images_out_file, fs::read_dir(&images_dir).unwrap());
+    write_image_names(&mut images_out_file, fs::read_dir(&images_dir).unwrap());
 
     let sprites_dir = resources_dir.join("sprite");
     let sprites_out_path = dest_path.join("sprites.rs");
     let mut sprites_out_file = File::create(sprites_out_path).unwrap();
-    write_sprites(&mut sprites_out_file, fs::read_dir(sprites_dir).unwrap());
+    write_sprites(&mut sprites_out_file, fs::read_dir(&sprites_dir).unwrap());
+    write_sprite_sources(&mut sprites_out_file, fs::read_dir(sprites_dir).unwrap());
 
     let fonts_dir = resources_dir.join("font");
     let fonts_out_path = dest_path.join("fonts.rs");
     let mut fonts_out_file = File::create(fonts_out_path).unwrap();
     write_fonts(&mut fonts_out_file, fs::read_dir(fonts_dir).unwrap());
 
-    let tile_sets_dir = resources_dir.join("tile_set");
+    let tile_grids_dir = resources_dir.join("tile_grid");
     let tile_sets_out_path = dest_path.join("tile_sets.rs");
     let mut tile_sets_out_file = File::create(tile_sets_out_path).unwrap();
-    write_tile_sets(&mut tile_sets_out_file, fs::read_dir(tile_sets_dir).unwrap());
+    write_tiled_tile_sets(&mut tile_sets_out_file, &images_dir, fs::read_dir(&tile_grids_dir).unwrap());
 
-    let tile_grids_dir = resources_dir.join("tile_grid");
     let tile_grids_out_path = dest_path.join("tile_grids.rs");
     let mut tile_grids_out_file = File::create(tile_grids_out_path).unwrap();
-    write_tile_grids(&mut tile_grids_out_file, fs::read_dir(tile_grids_dir).unwrap());
+    write_tile_grids(&mut tile_grids_out_file, fs::read_dir(&tile_grids_dir).unwrap());
+    write_object_names(&mut tile_grids_out_file, fs::read_dir(&tile_grids_dir).unwrap());
 
     let dialogs_dir = resources_dir.join("dialog");
+    let rules_out_path = dest_path.join("rules.rs");
+    let mut rules_out_file = File::create(rules_out_path).unwrap();
+    write_rules(&mut rules_out_file, &dialogs_dir.join("rules.toml"));
+
+    let speakers_out_path = dest_path.join("speakers.rs");
+    let mut speakers_out_file = File::create(speakers_out_path).unwrap();
+    write_speakers(&mut speakers_out_file, &dialogs_dir.join("speakers.toml"));
+
     let dialogs_out_path = dest_path.join("dialogs.rs");
     let mut dialogs_out_file = File::create(dialogs_out_path).unwrap();
     write_inks(&mut dialogs_out_file, fs::read_dir(dialogs_dir).unwrap());
@@ -0,0 +1,55 @@
+use std::{
+    io::Write,
+    path::Path,
+};
+
+use super::schema::*;
+use super::diagnostic::read_toml;
+
+/// Generates the table of text style rules declared in the dialog TOML, which is used when parsing
+/// the text of the dialogs.
+pub fn write_rules<W: Write>(file: &mut W, path: &Path) {
+    let dialog: DialogSpec = read_toml(path).unwrap();
+    writeln!(file, "lazy_static! {{").unwrap();
+    writeln!(file, "static ref RULES: HashMap<&'static str, Attributes> = {{").unwrap();
+    writeln!(file, "let mut map = HashMap::new();").unwrap();
+    for rule in &dialog.rules {
+        let style = rule.resolve(path).unwrap();
+        let mut fields = vec![];
+        if let Some(family) = &style.family {
+            fields.push(format!("family: Some({:?})", family));
+        }
+        if let Some(font_style) = &style.style {
+            fields.push(format!("style: Some({:?})", font_style));
+        }
+        if let Some(size) = style.size {
+            fields.push(format!("size: Some({})", size));
+        }
+        if let Some(color) = style.color {
+            fields.push(format!("color: Some(0x{:08x})", color));
+        }
+        if let Some(underline) = style.underline {
+            fields.push(format!("underline: Some({})", underline));
+        }
+        if let Some(outline) = style.outline {
+            fields.push(format!("outline: Some(0x{:08x})", outline));
+        }
+        if let Some(background) = style.background {
+            fields.push(format!("background: Some(0x{:08x})", background));
+        }
+        if let Some(shake) = style.shake {
+            fields.push(format!("shake: Some({})", shake));
+        }
+        if let Some(wave) = style.wave {
+            fields.push(format!("wave: Some({})", wave));
+        }
+        if let Some(fade) = style.fade {
+            fields.push(format!("fade: Some({})", fade));
+        }
+        fields.push("..Attributes::default()".to_owned());
+        writeln!(file, "map.insert({:?}, Attributes {{ {} }});", rule.name, fields.join(", ")).unwrap();
+    }
+    writeln!(file, "map").unwrap();
+    writeln!(file, "}};").unwrap();
+    writeln!(file, "}}").unwrap();
+}
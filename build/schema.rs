@@ -1,7 +1,146 @@
+use std::{
+    collections::{HashMap, BTreeMap},
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use flate2::read::{ZlibDecoder, GzDecoder};
+
+use super::diagnostic::*;
+
+/// A sprite, whose frames are either listed one by one, or cut from a `grid`. A sprite can also
+/// name some `animations`, each of which is a range of its frames.
 #[derive(Deserialize)]
 pub struct SpriteSpec {
     pub image: String,
+    pub dimensions: Option<Dimen>,
+    #[serde(default)]
     pub frames: Vec<[u32; 4]>,
+    pub grid: Option<SpriteGridSpec>,
+    #[serde(default)]
+    pub animations: BTreeMap<String, SpriteAnimationSpec>,
+}
+
+impl SpriteSpec {
+    pub fn load(path: &Path) -> Result<SpriteSheet> {
+        read_toml::<SpriteSpec>(path)?.resolve(path)
+    }
+
+    pub fn resolve(self, file: &Path) -> Result<SpriteSheet> {
+        let (frames, columns) = match self.grid {
+            Some(grid) => {
+                if !self.frames.is_empty() {
+                    return Err(Diagnostic::new(file, "frames", "a sprite may have either a grid or a list of frames, not both"));
+                }
+                (grid.frames(), Some(grid.columns))
+            }
+            None => (self.frames, None),
+        };
+        let animations = self.animations
+            .into_iter()
+            .map(|(name, animation)| {
+                let animation = animation.resolve(file, &format!("animations.{}", name), columns)?;
+                Ok((name, animation))
+            })
+            .collect::<Result<_>>()?;
+        Ok(SpriteSheet {
+            image: self.image,
+            dimensions: self.dimensions,
+            frames,
+            animations,
+        })
+    }
+}
+
+/// A sprite sheet where every frame is the same size, laid out in rows and columns.
+#[derive(Copy, Clone, Deserialize)]
+pub struct SpriteGridSpec {
+    pub size: Dimen,
+    pub columns: u32,
+    pub rows: u32,
+    #[serde(default)]
+    pub margin: u32,
+    #[serde(default)]
+    pub spacing: u32,
+}
+
+impl SpriteGridSpec {
+    /// The frames of the grid, row by row.
+    pub fn frames(&self) -> Vec<[u32; 4]> {
+        (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (row, column)))
+            .map(|(row, column)| [
+                self.margin + column * (self.size.width + self.spacing),
+                self.margin + row * (self.size.height + self.spacing),
+                self.size.width,
+                self.size.height,
+            ])
+            .collect()
+    }
+}
+
+/// A named animation of a sprite. The `frames` are a range (e.g. `"1..9"`) and the `idle` frame
+/// is shown when the animation is stopped. When a `row` of the grid is given, the frames are
+/// counted from the start of that row rather than from the first frame of the sprite.
+#[derive(Deserialize)]
+pub struct SpriteAnimationSpec {
+    pub row: Option<u32>,
+    pub frames: String,
+    pub idle: Option<u32>,
+}
+
+impl SpriteAnimationSpec {
+    fn resolve(self, file: &Path, field: &str, columns: Option<u32>) -> Result<SpriteAnimation> {
+        let offset = match (self.row, columns) {
+            (Some(row), Some(columns)) => row * columns,
+            (Some(..), None) => return Err(Diagnostic::new(file, format!("{}.row", field), "only a sprite with a grid has rows")),
+            (None, _) => 0,
+        };
+        let range: Vec<_> = self.frames.split("..").collect();
+        if range.len() != 2 {
+            return Err(Diagnostic::new(file, format!("{}.frames", field), format!("{:?} is not a range like \"1..9\"", self.frames)));
+        }
+        let start: u32 = parse_number(file, &format!("{}.frames", field), range[0])?;
+        let end: u32 = parse_number(file, &format!("{}.frames", field), range[1])?;
+        if start >= end {
+            return Err(Diagnostic::new(file, format!("{}.frames", field), format!("{:?} does not contain any frames", self.frames)));
+        }
+        Ok(SpriteAnimation {
+            idle: offset + self.idle.unwrap_or(start),
+            start: offset + start,
+            end: offset + end,
+        })
+    }
+}
+
+pub struct SpriteSheet {
+    pub image: String,
+    pub dimensions: Option<Dimen>,
+    pub frames: Vec<[u32; 4]>,
+    pub animations: BTreeMap<String, SpriteAnimation>,
+}
+
+impl SpriteSheet {
+    /// The name of the struct generated for the animations of the sprite with the given constant
+    /// name, e.g. `MaleWalkcycleAnim` for `MALE_WALKCYCLE`.
+    pub fn animations_struct_name(const_name: &str) -> String {
+        let mut name: String = const_name
+            .split('_')
+            .filter(|word| !word.is_empty())
+            .map(|word| word[..1].to_uppercase() + &word[1..].to_lowercase())
+            .collect();
+        name.push_str("Anim");
+        name
+    }
+}
+
+/// The absolute frames of a sprite animation.
+#[derive(Copy, Clone)]
+pub struct SpriteAnimation {
+    pub idle: u32,
+    pub start: u32,
+    pub end: u32,
 }
 
 #[derive(Deserialize)]
@@ -40,16 +179,6 @@ impl ::std::fmt::Display for Point {
     }
 }
 
-#[derive(Deserialize)]
-pub struct TileSetSpec {
-    pub image: String,
-    pub count: u32,
-    pub per_row: u32,
-    pub size: Dimen,
-    pub origin: Point,
-    pub spacing: Dimen,
-}
-
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum Tile {
@@ -75,6 +204,8 @@ pub struct TiledTMXSpec {
     pub tilesets: Vec<TiledTMXTileset>,
     #[serde(rename = "layer", default)]
     pub layers: Vec<TiledTMXLayer>,
+    #[serde(rename = "objectgroup", default)]
+    pub object_groups: Vec<TiledTMXObjectGroup>,
 }
 
 pub struct TiledSpec {
@@ -84,76 +215,525 @@ pub struct TiledSpec {
     pub tileheight: u32,
     pub tilesets: Vec<TiledTileset>,
     pub layers: Vec<TiledLayer>,
+    pub object_groups: Vec<TiledObjectGroup>,
 }
 
 impl TiledTMXSpec {
-    pub fn resolve(self) -> TiledSpec {
-        TiledSpec {
-            width: self.width.parse::<u32>().unwrap(),
-            height: self.height.parse::<u32>().unwrap(),
-            tilewidth: self.tilewidth.parse::<u32>().unwrap(),
-            tileheight: self.tileheight.parse::<u32>().unwrap(),
-            tilesets: self.tilesets.into_iter().map(TiledTMXTileset::resolve).collect(),
-            layers: self.layers.into_iter().map(TiledTMXLayer::resolve).collect(),
-        }
+    /// Reads and resolves the TMX file at `path`.
+    pub fn load(path: &Path) -> Result<TiledSpec> {
+        let xml = fs::read_to_string(path)
+            .map_err(|error| Diagnostic::new(path, "", format!("could not be read: {}", error)))?;
+        parse_xml::<TiledTMXSpec>(path, &xml_tiles_to_csv(&xml))?.resolve(path)
+    }
+
+    /// Resolves the map, where `file` is the TMX file it was read from.
+    pub fn resolve(self, file: &Path) -> Result<TiledSpec> {
+        Ok(TiledSpec {
+            width: parse_number(file, "width", &self.width)?,
+            height: parse_number(file, "height", &self.height)?,
+            tilewidth: parse_number(file, "tilewidth", &self.tilewidth)?,
+            tileheight: parse_number(file, "tileheight", &self.tileheight)?,
+            tilesets: self.tilesets
+                .into_iter()
+                .map(|tileset| tileset.resolve(file))
+                .collect::<Result<_>>()?,
+            layers: self.layers
+                .into_iter()
+                .map(|layer| layer.resolve(file))
+                .collect::<Result<_>>()?,
+            object_groups: self.object_groups
+                .into_iter()
+                .map(|group| group.resolve(file))
+                .collect::<Result<_>>()?,
+        })
     }
 }
 
 #[derive(Deserialize)]
 pub struct TiledTMXTileset {
-    pub name: String,
     pub firstgid: String,
-    pub tilecount: String,
+    pub source: Option<String>,
+    pub name: Option<String>,
+    pub tilewidth: Option<String>,
+    pub tileheight: Option<String>,
+    pub tilecount: Option<String>,
+    pub columns: Option<String>,
+    pub margin: Option<String>,
+    pub spacing: Option<String>,
+    pub image: Option<TiledTMXImage>,
+    #[serde(rename = "tile", default)]
+    pub tiles: Vec<TiledTMXTile>,
 }
 
 impl TiledTMXTileset {
-    fn resolve(self) -> TiledTileset {
-        TiledTileset {
-            name: self.name,
-            firstgid: self.firstgid.parse::<u32>().unwrap(),
-            tilecount: self.tilecount.parse::<u32>().unwrap(),
+    /// Resolves a tile set of a map, which is either embedded in the map itself, or in an external
+    /// TSX file referenced by the `source` attribute. Paths are relative to the file they are
+    /// written in.
+    fn resolve(self, map: &Path) -> Result<TiledTileset> {
+        let firstgid = parse_number(map, "tileset.firstgid", &self.firstgid)?;
+        if let Some(source) = self.source {
+            let tsx_path = map.parent().unwrap().join(source);
+            read_xml::<TiledTSXSpec>(&tsx_path)?.resolve(&tsx_path, firstgid)
+        } else {
+            let name = self.name
+                .ok_or_else(|| Diagnostic::new(map, format!("tileset[firstgid={}].name", firstgid), "embedded tile sets must have a name"))?;
+            let missing = |field: &str| Diagnostic::new(map, format!("tileset[{}].{}", name, field), "embedded tile sets must have this attribute");
+            TiledTSXSpec {
+                tilewidth: self.tilewidth.ok_or_else(|| missing("tilewidth"))?,
+                tileheight: self.tileheight.ok_or_else(|| missing("tileheight"))?,
+                tilecount: self.tilecount.ok_or_else(|| missing("tilecount"))?,
+                columns: self.columns.ok_or_else(|| missing("columns"))?,
+                margin: self.margin,
+                spacing: self.spacing,
+                image: self.image.ok_or_else(|| missing("image"))?,
+                tiles: self.tiles,
+                name,
+            }.resolve(map, firstgid)
         }
     }
 }
 
+/// The root of a TSX file. This is the same as an embedded tile set, but without the `firstgid`,
+/// which is only known by the map that uses it.
+#[derive(Deserialize)]
+pub struct TiledTSXSpec {
+    pub name: String,
+    pub tilewidth: String,
+    pub tileheight: String,
+    pub tilecount: String,
+    pub columns: String,
+    pub margin: Option<String>,
+    pub spacing: Option<String>,
+    pub image: TiledTMXImage,
+    #[serde(rename = "tile", default)]
+    pub tiles: Vec<TiledTMXTile>,
+}
+
+impl TiledTSXSpec {
+    /// Resolves the tile set, where `file` is the TMX or TSX file it was written in.
+    fn resolve(self, file: &Path, firstgid: u32) -> Result<TiledTileset> {
+        let field = |attribute: &str| format!("tileset[{}].{}", self.name, attribute);
+        let image = file.parent().unwrap().join(&self.image.source);
+        let image = image
+            .canonicalize()
+            .map_err(|_| Diagnostic::new(file, field("image.source"), format!("{} does not exist", image.display())))?;
+        Ok(TiledTileset {
+            firstgid,
+            tilewidth: parse_number(file, &field("tilewidth"), &self.tilewidth)?,
+            tileheight: parse_number(file, &field("tileheight"), &self.tileheight)?,
+            tilecount: parse_number(file, &field("tilecount"), &self.tilecount)?,
+            columns: parse_number(file, &field("columns"), &self.columns)?,
+            margin: self.margin.as_ref().map(|margin| parse_number(file, &field("margin"), margin)).unwrap_or(Ok(0))?,
+            spacing: self.spacing.as_ref().map(|spacing| parse_number(file, &field("spacing"), spacing)).unwrap_or(Ok(0))?,
+            animations: self.tiles
+                .iter()
+                .filter_map(|tile| tile.animation.as_ref().map(|animation| (tile, animation)))
+                .map(|(tile, animation)| {
+                    let field = field(&format!("tile[{}].animation", tile.id));
+                    let frames = animation.frames
+                        .iter()
+                        .map(|frame| Ok(TiledFrame {
+                            tileid: parse_number(file, &format!("{}.frame.tileid", field), &frame.tileid)?,
+                            duration: parse_number(file, &format!("{}.frame.duration", field), &frame.duration)?,
+                        }))
+                        .collect::<Result<_>>()?;
+                    Ok((parse_number(file, &field, &tile.id)?, frames))
+                })
+                .collect::<Result<_>>()?,
+            properties: self.tiles
+                .iter()
+                .filter_map(|tile| tile.properties.as_ref().map(|properties| (tile, properties)))
+                .map(|(tile, properties)| {
+                    let id = parse_number(file, &field(&format!("tile[{}].properties", tile.id)), &tile.id)?;
+                    let properties = properties.properties
+                        .iter()
+                        .map(|property| (property.name.clone(), property.value.clone()))
+                        .collect();
+                    Ok((id, properties))
+                })
+                .collect::<Result<_>>()?,
+            image,
+            name: self.name,
+        })
+    }
+}
+
+/// The extra information for a single tile of a tile set.
+#[derive(Deserialize)]
+pub struct TiledTMXTile {
+    pub id: String,
+    pub animation: Option<TiledTMXAnimation>,
+    pub properties: Option<TiledTMXProperties>,
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXAnimation {
+    #[serde(rename = "frame", default)]
+    pub frames: Vec<TiledTMXFrame>,
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXFrame {
+    pub tileid: String,
+    pub duration: String,
+}
+
+/// One frame of an animated tile, which shows the tile `tileid` of the same tile set for
+/// `duration` milliseconds.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct TiledFrame {
+    pub tileid: u32,
+    pub duration: u32,
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXImage {
+    pub source: String,
+}
+
 pub struct TiledTileset {
     pub name: String,
     pub firstgid: u32,
+    pub tilewidth: u32,
+    pub tileheight: u32,
     pub tilecount: u32,
+    pub columns: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    pub image: PathBuf,
+    pub animations: BTreeMap<u32, Vec<TiledFrame>>,
+    pub properties: BTreeMap<u32, BTreeMap<String, String>>,
+}
+
+impl TiledTileset {
+    /// The name of the `TileSet` constant generated for this tile set.
+    pub fn const_name(&self) -> String {
+        self.name
+            .chars()
+            .map(|ch| if ch.is_ascii_alphanumeric() { ch.to_ascii_uppercase() } else { '_' })
+            .collect()
+    }
+
+    /// Whether two tile sets would generate the same `TileSet`, regardless of where they start in
+    /// their maps.
+    pub fn same_as(&self, other: &TiledTileset) -> bool {
+        self.name == other.name
+            && self.tilewidth == other.tilewidth
+            && self.tileheight == other.tileheight
+            && self.tilecount == other.tilecount
+            && self.columns == other.columns
+            && self.margin == other.margin
+            && self.spacing == other.spacing
+            && self.image == other.image
+            && self.animations == other.animations
+            && self.properties == other.properties
+    }
+
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.firstgid && gid < self.firstgid + self.tilecount
+    }
+
+    /// The name of the `TileAnimation` constant generated for an animated tile of this tile set.
+    pub fn animation_const_name(&self, id: u32) -> String {
+        format!("{}_ANIMATION_{}", self.const_name(), id)
+    }
+
+    /// The name of the `TileProperties` constant generated for a tile of this tile set which has
+    /// custom properties.
+    pub fn properties_const_name(&self, id: u32) -> String {
+        format!("{}_PROPERTIES_{}", self.const_name(), id)
+    }
+
+    /// Whether a tile of this tile set has been marked as solid, in which case it gets a `Wall`
+    /// just like the tiles of a `COLLISIONS` layer do.
+    pub fn is_solid(&self, id: u32) -> bool {
+        self.properties
+            .get(&id)
+            .and_then(|properties| properties.get("solid"))
+            .map(|solid| solid == "true")
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Deserialize)]
 pub struct TiledTMXLayer {
     pub name: String,
+    pub properties: Option<TiledTMXProperties>,
     pub data: TiledTMXData,
 }
 
 impl TiledTMXLayer {
-    fn resolve(self) -> TiledLayer {
-        TiledLayer {
+    fn resolve(self, file: &Path) -> Result<TiledLayer> {
+        let properties = self.properties.map(TiledTMXProperties::resolve).unwrap_or_default();
+        let depth = match properties.get("depth") {
+            Some(depth) => Some(parse_number(file, &format!("layer[{}].depth", self.name), depth)?),
+            None => None,
+        };
+        Ok(TiledLayer {
+            depth,
+            tiles: self.data.decode(file, &format!("layer[{}].data", self.name))?
+                .into_iter()
+                .map(TiledTile::from_gid)
+                .collect(),
             name: self.name,
-            tiles: self.data.value
-                .split(",")
-                .map(|index| index.trim().parse::<u32>().unwrap())
-                .collect()
-        }
+        })
     }
 }
 
+/// A layer of a map. Only the layers with a `depth` property are drawn, at that depth in the
+/// `TileLayers`, so the `COLLISIONS` layer has none.
 pub struct TiledLayer {
     pub name: String,
-    pub tiles: Vec<u32>,
+    pub depth: Option<i32>,
+    pub tiles: Vec<TiledTile>,
+}
+
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY: u32 = 0x20000000;
+
+/// A single tile of a layer. Tiled stores the flip flags in the high bits of the GID, so they are
+/// masked off here, leaving a GID that can actually be used to find the tile set. The engine cannot
+/// draw flipped tiles yet, so flipped tiles are drawn the right way around.
+#[derive(Copy, Clone)]
+pub struct TiledTile {
+    pub gid: u32,
+}
+
+impl TiledTile {
+    fn from_gid(gid: u32) -> Self {
+        TiledTile {
+            gid: gid & !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 pub struct TiledTMXData {
+    pub encoding: Option<String>,
+    pub compression: Option<String>,
     #[serde(rename = "$value")]
     pub value: String,
 }
 
+impl TiledTMXData {
+    /// Decodes the raw GIDs of a layer, according to the encoding and compression that Tiled saved
+    /// it with. Layers saved as XML have already been rewritten as csv by `xml_tiles_to_csv`.
+    fn decode(&self, file: &Path, field: &str) -> Result<Vec<u32>> {
+        match (self.encoding.as_ref().map(String::as_str), self.compression.as_ref().map(String::as_str)) {
+            (Some("csv"), None) => self.value
+                .split(",")
+                .map(|index| parse_number(file, field, index))
+                .collect(),
+            (Some("base64"), compression) => {
+                let data: String = self.value.chars().filter(|ch| !ch.is_whitespace()).collect();
+                let bytes = base64::decode(&data)
+                    .map_err(|error| Diagnostic::new(file, field, format!("contains invalid base64 data: {}", error)))?;
+                let bytes = match compression {
+                    None => bytes,
+                    Some("zlib") => {
+                        let mut decoded = vec![];
+                        ZlibDecoder::new(&bytes[..])
+                            .read_to_end(&mut decoded)
+                            .map_err(|error| Diagnostic::new(file, field, format!("contains invalid zlib data: {}", error)))?;
+                        decoded
+                    }
+                    Some("gzip") => {
+                        let mut decoded = vec![];
+                        GzDecoder::new(&bytes[..])
+                            .read_to_end(&mut decoded)
+                            .map_err(|error| Diagnostic::new(file, field, format!("contains invalid gzip data: {}", error)))?;
+                        decoded
+                    }
+                    Some(compression) => return Err(Diagnostic::new(file, field, format!("uses unsupported compression {:?}", compression))),
+                };
+                if bytes.len() % 4 != 0 {
+                    return Err(Diagnostic::new(file, field, format!("is not a whole number of tiles ({} bytes)", bytes.len())));
+                }
+                // GIDs are stored as little-endian unsigned 32 bit integers
+                Ok(bytes
+                    .chunks(4)
+                    .map(|gid| gid[0] as u32 | (gid[1] as u32) << 8 | (gid[2] as u32) << 16 | (gid[3] as u32) << 24)
+                    .collect())
+            }
+            (encoding, compression) => Err(Diagnostic::new(
+                file,
+                field,
+                format!(
+                    "uses unsupported encoding {:?} with compression {:?}. Save it as XML, csv or base64 (optionally zlib or gzip compressed)",
+                    encoding,
+                    compression,
+                ),
+            )),
+        }
+    }
+}
+
+/// Rewrites the layers that Tiled saved with its XML encoding, where each tile is a
+/// `<tile gid="..."/>` element, as csv. serde_xml_rs reads the contents of the `<data>` as text,
+/// so those elements would be lost otherwise.
+fn xml_tiles_to_csv(xml: &str) -> String {
+    let mut csv = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some((start, end)) = find_tag(rest, "data") {
+        let tag = &rest[start..end];
+        csv.push_str(&rest[..start]);
+        let close = rest[end..].find("</data>").map(|close| end + close);
+        match close {
+            Some(close) if !tag.ends_with("/>") && attribute(tag, "encoding").is_none() => {
+                let mut tiles = &rest[end..close];
+                let mut gids = vec![];
+                while let Some((start, end)) = find_tag(tiles, "tile") {
+                    gids.push(attribute(&tiles[start..end], "gid").unwrap_or("0"));
+                    tiles = &tiles[end..];
+                }
+                csv.push_str(tag.trim_end_matches('>'));
+                csv.push_str(" encoding=\"csv\">");
+                csv.push_str(&gids.join(","));
+                csv.push_str("</data>");
+                rest = &rest[close + "</data>".len()..];
+            }
+            _ => {
+                csv.push_str(tag);
+                rest = &rest[end..];
+            }
+        }
+    }
+    csv.push_str(rest);
+    csv
+}
+
+/// The start and end of the first opening tag of the element `name`.
+fn find_tag(xml: &str, name: &str) -> Option<(usize, usize)> {
+    let pattern = format!("<{}", name);
+    let mut offset = 0;
+    while let Some(start) = xml[offset..].find(&pattern).map(|start| offset + start) {
+        let after = start + pattern.len();
+        let ends_name = xml[after..]
+            .chars()
+            .next()
+            .map(|ch| ch.is_whitespace() || ch == '>' || ch == '/')
+            .unwrap_or(false);
+        if ends_name {
+            return xml[after..].find('>').map(|end| (start, after + end + 1));
+        }
+        offset = after;
+    }
+    None
+}
+
+/// The value of an attribute of an opening tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let preceded = rest[..index].chars().next_back().map(char::is_whitespace).unwrap_or(false);
+        let value = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        if !preceded || !value.starts_with('=') {
+            continue;
+        }
+        let value = value[1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => quote,
+            _ => continue,
+        };
+        return value[1..].find(quote).map(|end| &value[1..end + 1]);
+    }
+    None
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXObjectGroup {
+    pub name: String,
+    #[serde(rename = "object", default)]
+    pub objects: Vec<TiledTMXObject>,
+}
+
+impl TiledTMXObjectGroup {
+    fn resolve(self, file: &Path) -> Result<TiledObjectGroup> {
+        let field = format!("objectgroup[{}]", self.name);
+        Ok(TiledObjectGroup {
+            objects: self.objects
+                .into_iter()
+                .map(|object| object.resolve(file, &field))
+                .collect::<Result<_>>()?,
+            name: self.name,
+        })
+    }
+}
+
+pub struct TiledObjectGroup {
+    pub name: String,
+    pub objects: Vec<TiledObject>,
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXObject {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    pub x: String,
+    pub y: String,
+    pub width: Option<String>,
+    pub height: Option<String>,
+    pub properties: Option<TiledTMXProperties>,
+}
+
+impl TiledTMXObject {
+    fn resolve(self, file: &Path, group: &str) -> Result<TiledObject> {
+        let field = |attribute: &str| format!("{}.object[{}].{}", group, self.name, attribute);
+        // Tiled allows sub-pixel object positions, but everything in the game is on whole pixels
+        let pixels = |attribute: &str, value: &str| parse_number::<f32>(file, &field(attribute), value).map(f32::round);
+        Ok(TiledObject {
+            x: pixels("x", &self.x)? as i32,
+            y: pixels("y", &self.y)? as i32,
+            width: self.width.as_ref().map(|width| pixels("width", width)).unwrap_or(Ok(0f32))? as u32,
+            height: self.height.as_ref().map(|height| pixels("height", height)).unwrap_or(Ok(0f32))? as u32,
+            properties: self.properties.map(TiledTMXProperties::resolve).unwrap_or_default(),
+            name: self.name,
+            kind: self.kind,
+        })
+    }
+}
+
+pub struct TiledObject {
+    pub name: String,
+    pub kind: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub properties: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXProperties {
+    #[serde(rename = "property", default)]
+    pub properties: Vec<TiledTMXProperty>,
+}
+
+impl TiledTMXProperties {
+    fn resolve(self) -> HashMap<String, String> {
+        self.properties
+            .into_iter()
+            .map(|property| (property.name, property.value))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TiledTMXProperty {
+    pub name: String,
+    pub value: String,
+}
+
+/// The dialog configuration, which declares the text style rules that may be used in the ink
+/// dialogs (e.g. `<thought:...>`).
 #[derive(Deserialize)]
 pub struct DialogSpec {
     pub rules: Vec<Rule>,
+    #[serde(default)]
     pub messages: Vec<Message>,
 }
 
@@ -163,6 +743,74 @@ pub struct Rule {
     pub attributes: Vec<Attribute>,
 }
 
+impl Rule {
+    pub fn resolve(&self, file: &Path) -> Result<TextStyle> {
+        let mut style = TextStyle::default();
+        for attribute in &self.attributes {
+            let field = format!("rules[{}].{}", self.name, attribute.name);
+            let value = attribute.value.clone();
+            match attribute.name.as_str() {
+                "family" => style.family = Some(value),
+                "style" => style.style = Some(value),
+                "size" => style.size = Some(parse_number(file, &field, &value)?),
+                "color" => style.color = Some(parse_color(file, &field, &value)?),
+                "underline" => style.underline = Some(parse_bool(file, &field, &value)?),
+                "outline" => style.outline = Some(parse_color(file, &field, &value)?),
+                "background" => style.background = Some(parse_color(file, &field, &value)?),
+                "shake" => style.shake = Some(parse_number(file, &field, &value)?),
+                "wave" => style.wave = Some(parse_number(file, &field, &value)?),
+                "fade" => style.fade = Some(parse_number(file, &field, &value)?),
+                name => return Err(Diagnostic::new(
+                    file,
+                    field,
+                    format!(
+                        "{:?} is not an attribute, expected one of family, style, size, color, underline, outline, background, shake, wave, or fade",
+                        name,
+                    ),
+                )),
+            }
+        }
+        Ok(style)
+    }
+}
+
+fn parse_bool(file: &Path, field: &str, value: &str) -> Result<bool> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Diagnostic::new(file, field, format!("expected true or false, but found {:?}", value)))
+}
+
+/// A colour written as `#rrggbb` or `#rrggbbaa`, as a `0xrrggbbaa` number.
+fn parse_color(file: &Path, field: &str, value: &str) -> Result<u32> {
+    let hex = value.trim();
+    let invalid = || Diagnostic::new(file, field, format!("{:?} is not a colour like \"#rrggbb\" or \"#rrggbbaa\"", value));
+    if !hex.starts_with('#') {
+        return Err(invalid());
+    }
+    let color = u32::from_str_radix(&hex[1..], 16).map_err(|_| invalid())?;
+    match hex.len() {
+        7 => Ok(color << 8 | 0xff),
+        9 => Ok(color),
+        _ => Err(invalid()),
+    }
+}
+
+/// The attributes of a rule. Each one that is not set is inherited from the surrounding text.
+#[derive(Default)]
+pub struct TextStyle {
+    pub family: Option<String>,
+    pub style: Option<String>,
+    pub size: Option<u32>,
+    pub color: Option<u32>,
+    pub underline: Option<bool>,
+    pub outline: Option<u32>,
+    pub background: Option<u32>,
+    pub shake: Option<u32>,
+    pub wave: Option<u32>,
+    pub fade: Option<u32>,
+}
+
 #[derive(Clone, Deserialize)]
 pub struct Attribute {
     pub name: String,
@@ -174,3 +822,21 @@ pub struct Message {
     pub speaker: Option<String>,
     pub message: String,
 }
+
+/// The registry of the speakers that have a portrait in the dialog box.
+#[derive(Deserialize)]
+pub struct SpeakersSpec {
+    #[serde(default)]
+    pub speakers: Vec<SpeakerSpec>,
+}
+
+/// A speaker, as named at the start of a line (e.g. `Mystery Man:`), and their portrait. The
+/// portrait is a sprite (e.g. `portrait::MALE`), with a frame for each of the speaker's
+/// expressions. A line that does not pick an expression (e.g. `You[worried]:`) is shown with the
+/// `neutral` one.
+#[derive(Deserialize)]
+pub struct SpeakerSpec {
+    pub name: String,
+    pub portrait: String,
+    pub expressions: BTreeMap<String, u32>,
+}
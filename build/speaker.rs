@@ -0,0 +1,28 @@
+use std::{
+    io::Write,
+    path::Path,
+};
+
+use super::schema::*;
+use super::diagnostic::read_toml;
+
+/// Generates the table of speakers declared in the speaker registry, with the portrait sprite and
+/// the frame of each expression.
+pub fn write_speakers<W: Write>(file: &mut W, path: &Path) {
+    let speakers: SpeakersSpec = read_toml(path).unwrap();
+    writeln!(file, "pub const SPEAKERS: &[Speaker] = &[").unwrap();
+    for speaker in &speakers.speakers {
+        writeln!(
+            file,
+            "Speaker::new({:?}, &sprite::{}, &[{}]),",
+            speaker.name,
+            speaker.portrait,
+            speaker.expressions
+                .iter()
+                .map(|(name, frame)| format!("({:?}, {})", name, frame))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ).unwrap();
+    }
+    writeln!(file, "];").unwrap();
+}
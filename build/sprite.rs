@@ -4,8 +4,6 @@ use std::{
     ffi::OsStr,
 };
 
-use toml::from_str;
-
 use super::schema::*;
 
 pub fn write_sprites<'a, W: Write>(file: &mut W, paths: ReadDir) {
@@ -13,20 +11,69 @@ pub fn write_sprites<'a, W: Write>(file: &mut W, paths: ReadDir) {
         let path = path.unwrap().path();
         if path.is_dir() {
             writeln!(file, "pub mod {} {{", path.file_name().unwrap().to_str().unwrap().to_owned().to_lowercase()).unwrap();
-            writeln!(file, "use super::{{image, Sprite, Rect}};").unwrap();
+            writeln!(file, "use super::{{image, Sprite, SpriteAnimation, Rect}};").unwrap();
             let sub_paths = fs::read_dir(path).unwrap();
             write_sprites(file, sub_paths);
             writeln!(file, "}}").unwrap();
         } else if path.extension() == Some(&OsStr::new("toml")) {
             let name = path.file_stem().unwrap();
             let const_name = name.to_str().unwrap().to_owned().to_uppercase();
-            let toml_str = fs::read_to_string(&path).unwrap();
-            let sprite: SpriteSpec = from_str(&toml_str).unwrap();
+            let sprite = SpriteSpec::load(&path).unwrap();
             writeln!(file, "pub const {}: Sprite = Sprite::new(image::{}, &[", const_name, sprite.image).unwrap();
-            for [x, y, w, h] in sprite.frames {
+            for [x, y, w, h] in &sprite.frames {
                 writeln!(file, "Rect::new({}, {}, {}, {}),", x, y, w, h).unwrap();
             }
             writeln!(file, "]);").unwrap();
+            write_animations(file, &const_name, &sprite);
+        }
+    }
+}
+
+/// Generates a struct with a field for each of the named animations of a sprite, and a constant of
+/// that struct (e.g. `MALE_WALKCYCLE_ANIM.walk_up`).
+fn write_animations<W: Write>(file: &mut W, const_name: &str, sprite: &SpriteSheet) {
+    if sprite.animations.is_empty() {
+        return;
+    }
+    let struct_name = SpriteSheet::animations_struct_name(const_name);
+    writeln!(file, "pub struct {} {{", struct_name).unwrap();
+    for name in sprite.animations.keys() {
+        writeln!(file, "pub {}: SpriteAnimation,", name).unwrap();
+    }
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "pub const {}_ANIM: {} = {} {{", const_name, struct_name, struct_name).unwrap();
+    for (name, animation) in &sprite.animations {
+        writeln!(
+            file,
+            "{}: SpriteAnimation::new({}, {}..{}),",
+            name,
+            animation.idle,
+            animation.start,
+            animation.end,
+        ).unwrap();
+    }
+    writeln!(file, "}};").unwrap();
+}
+
+/// Generates the table of the source file of each sprite, which is used to find the sprites to
+/// replace when their source changes while hot reloading.
+pub fn write_sprite_sources<W: Write>(file: &mut W, paths: ReadDir) {
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "pub const SOURCES: &[(&str, &Sprite)] = &[").unwrap();
+    write_sprite_source_entries(file, paths, "");
+    writeln!(file, "];").unwrap();
+}
+
+fn write_sprite_source_entries<W: Write>(file: &mut W, paths: ReadDir, prefix: &str) {
+    for path in paths {
+        let path = path.unwrap().path();
+        if path.is_dir() {
+            let module = path.file_name().unwrap().to_str().unwrap().to_owned().to_lowercase();
+            let sub_paths = fs::read_dir(&path).unwrap();
+            write_sprite_source_entries(file, sub_paths, &format!("{}{}::", prefix, module));
+        } else if path.extension() == Some(&OsStr::new("toml")) {
+            let const_name = path.file_stem().unwrap().to_str().unwrap().to_owned().to_uppercase();
+            writeln!(file, "({:?}, &{}{}),", path.to_str().unwrap(), prefix, const_name).unwrap();
         }
     }
 }
@@ -1,20 +1,24 @@
 use std::{
-    fs::{self, File, ReadDir},
+    collections::BTreeSet,
+    env,
+    fs::{self, ReadDir},
     io::Write,
     ffi::OsStr,
+    path::Path,
 };
 
 use toml::from_str;
-use serde_xml_rs::deserialize;
 
+use super::diagnostic::{Diagnostic, Result};
 use super::schema::*;
+use super::collision::{merge_tiles, solid_tiles};
 
 pub fn write_tile_grids<'a, W: Write>(file: &mut W, paths: ReadDir) {
     for path in paths {
         let path = path.unwrap().path();
         if path.is_dir() {
             writeln!(file, "pub mod {} {{", path.file_name().unwrap().to_str().unwrap().to_owned().to_lowercase()).unwrap();
-            writeln!(file, "use super::{{tile_set, TileGrid, Tile, Point, Dimen, Wall, SceneBuilder, lazy_static}};").unwrap();
+            writeln!(file, "use super::{{tile_set, entity, scene, TileGrid, Tile, Point, Dimen, Wall, Door, StatePickup, State, MainState, TileAnimation, TilePropertyLayer, TileLayers, TileAnimations, TilePropertyLayers, SceneBuilder, lazy_static}};").unwrap();
             let sub_paths = fs::read_dir(path).unwrap();
             write_tile_grids(file, sub_paths);
             writeln!(file, "}}").unwrap();
@@ -44,57 +48,274 @@ pub fn write_tile_grids<'a, W: Write>(file: &mut W, paths: ReadDir) {
         } else if path.extension() == Some(&OsStr::new("tmx")) {
             let name = path.file_stem().unwrap();
             let mod_name = name.to_str().unwrap().to_owned().to_lowercase();
-            let xml_file = File::open(&path).unwrap();
-            let tiled_grid_spec: TiledTMXSpec = deserialize(&xml_file).unwrap();
-            let tile_grid = tiled_grid_spec.resolve();
+            let tile_grid = TiledTMXSpec::load(&path).unwrap();
             writeln!(file, "pub mod {} {{", mod_name).unwrap();
-            writeln!(file, "use super::{{tile_set, TileGrid, Tile, Point, Dimen, Wall, SceneBuilder, lazy_static}};").unwrap();
-            for layer in &tile_grid.layers {
+            writeln!(file, "use super::{{tile_set, entity, scene, TileGrid, Tile, Point, Dimen, Wall, Door, StatePickup, State, MainState, TileAnimation, TilePropertyLayer, TileLayers, TileAnimations, TilePropertyLayers, SceneBuilder, lazy_static}};").unwrap();
+            writeln!(file, "pub const SOURCE: &str = {:?};", path.to_str().unwrap()).unwrap();
+            writeln!(file, "pub const WIDTH: u32 = {};", tile_grid.width).unwrap();
+            writeln!(file, "pub const HEIGHT: u32 = {};", tile_grid.height).unwrap();
+            let layers: Vec<_> = tile_grid.layers
+                .iter()
+                .filter_map(|layer| layer.depth.map(|depth| (depth, layer.name.to_uppercase())))
+                .collect();
+            for layer in tile_grid.layers.iter().filter(|layer| layer.depth.is_some()) {
                 let const_name = layer.name.to_uppercase();
-                if const_name == "COLLISIONS" {
-                    writeln!(file, "pub fn collisions<'a>(builder: &mut SceneBuilder<'a>) {{").unwrap();
-                    writeln!(file, "builder").unwrap();
-                    for (index, tile) in layer.tiles.iter().enumerate() {
-                        if *tile != 0 {
-                            let x = index as u32 % tile_grid.width * tile_grid.tileheight;
-                            let y = index as u32 / tile_grid.width * tile_grid.tilewidth;
-                            let width = tile_grid.tilewidth;
-                            let height = tile_grid.tileheight;
-                            writeln!(file, ".add_entity(Wall({}, {}, {}, {}))", x, y, width, height).unwrap();
-                        }
-                    }
-                    writeln!(file, ";}}").unwrap();
-                } else {
-                    writeln!(
-                        file,
-                        "lazy_static! {{ pub static ref {}: TileGrid = TileGrid::new({}, {}, vec![{}]); }}",
-                        const_name,
-                        Point { x: 0, y: 0 },
-                        Dimen { width: tile_grid.width, height: tile_grid.height },
-                        layer.tiles
-                            .iter()
-                            .map(|index| {
-                                if *index == 0 {
-                                    "None".to_owned()
-                                } else {
-                                    let set = tile_grid
-                                        .tilesets
-                                        .iter()
-                                        .find(|set| *index >= set.firstgid && *index < set.firstgid + set.tilecount)
-                                        .unwrap();
-                                    format!(
-                                        "Some(Tile{{tile_set:&tile_set::{},index:{}}})",
-                                        set.name,
-                                        index - set.firstgid,
-                                    )
-                                }
-                            })
-                            .collect::<Vec<_>>()
-                            .join(",")
-                        ).unwrap();
-                }
+                writeln!(
+                    file,
+                    "lazy_static! {{ pub static ref {}_TILES: Vec<Option<Tile>> = vec![{}]; }}",
+                    const_name,
+                    layer.tiles
+                        .iter()
+                        .map(|tile| {
+                            if tile.gid == 0 {
+                                "None".to_owned()
+                            } else {
+                                let set = tile_grid
+                                    .tilesets
+                                    .iter()
+                                    .find(|set| set.contains(tile.gid))
+                                    .unwrap();
+                                format!("Some(Tile{{tile_set: &tile_set::{}, index: {}}})", set.const_name(), tile.gid - set.firstgid)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",")
+                    ).unwrap();
+                writeln!(
+                    file,
+                    "lazy_static! {{ pub static ref {}: TileGrid = TileGrid::new({}, {}, {}_TILES.clone()); }}",
+                    const_name,
+                    Point { x: 0, y: 0 },
+                    Dimen { width: tile_grid.width, height: tile_grid.height },
+                    const_name,
+                ).unwrap();
+                writeln!(
+                    file,
+                    "pub const {}_ANIMATIONS: &[(usize, &TileAnimation)] = &[{}];",
+                    const_name,
+                    layer.tiles
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, tile)| tile.gid != 0)
+                        .filter_map(|(index, tile)| {
+                            let set = tile_grid.tilesets.iter().find(|set| set.contains(tile.gid)).unwrap();
+                            let id = tile.gid - set.firstgid;
+                            if set.animations.contains_key(&id) {
+                                Some(format!("({}, &tile_set::{})", index, set.animation_const_name(id)))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ).unwrap();
+                writeln!(
+                    file,
+                    "pub const {}_PROPERTIES: TilePropertyLayer = TilePropertyLayer::new({}, {}, {}, &[{}]);",
+                    const_name,
+                    tile_grid.width,
+                    tile_grid.tilewidth,
+                    tile_grid.tileheight,
+                    layer.tiles
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, tile)| tile.gid != 0)
+                        .filter_map(|(index, tile)| {
+                            let set = tile_grid.tilesets.iter().find(|set| set.contains(tile.gid)).unwrap();
+                            let id = tile.gid - set.firstgid;
+                            if set.properties.contains_key(&id) {
+                                Some(format!("({}, &tile_set::{})", index, set.properties_const_name(id)))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ).unwrap();
             }
+            write_install(file, &layers);
+            write_collisions(file, &path, &tile_grid);
+            write_objects(file, &path, &tile_grid.object_groups).unwrap_or_else(|error| panic!("{}", error));
             writeln!(file, "}}").unwrap();
         }
     }
 }
+
+/// Generates the `LAYERS` of a map, the name of the layer drawn at each depth, and its `install`
+/// function, which puts those layers, along with their animations and tile properties, in place of
+/// the previous scene's.
+fn write_install<W: Write>(file: &mut W, layers: &[(i32, String)]) {
+    writeln!(
+        file,
+        "pub const LAYERS: &[(i32, &str)] = &[{}];",
+        layers
+            .iter()
+            .map(|(depth, name)| format!("({}, {:?})", depth, name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ).unwrap();
+    writeln!(file, "pub fn install<'a>(builder: &mut SceneBuilder<'a>) {{").unwrap();
+    writeln!(file, "{{").unwrap();
+    writeln!(file, "let mut layers = builder.get_resource_mut::<TileLayers>();").unwrap();
+    writeln!(file, "layers.clear();").unwrap();
+    for (depth, name) in layers {
+        writeln!(file, "layers.set({}, {}.clone());", depth, name).unwrap();
+    }
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "{{").unwrap();
+    writeln!(file, "let mut animations = builder.get_resource_mut::<TileAnimations>();").unwrap();
+    writeln!(file, "animations.clear();").unwrap();
+    for (depth, name) in layers {
+        writeln!(file, "animations.set({}, Dimen::new(WIDTH, HEIGHT), &{}_TILES, {}_ANIMATIONS);", depth, name, name).unwrap();
+    }
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "{{").unwrap();
+    writeln!(file, "let mut properties = builder.get_resource_mut::<TilePropertyLayers>();").unwrap();
+    writeln!(file, "properties.clear();").unwrap();
+    for (depth, name) in layers {
+        writeln!(file, "properties.set({}, &{}_PROPERTIES);", depth, name).unwrap();
+    }
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "builder.get_resource_mut::<crate::hot_reload::HotTileLayers>().watch(SOURCE, LAYERS);").unwrap();
+    writeln!(file, "}}").unwrap();
+}
+
+/// Generates the lookups that the hot reloader uses to spawn the objects of a reloaded map: the
+/// scenes that the doors of every map lead to, and the entities that they spawn, by the names that
+/// the maps give them.
+pub fn write_object_names<W: Write>(file: &mut W, paths: ReadDir) {
+    let mut scenes = BTreeSet::new();
+    let mut spawns = BTreeSet::new();
+    collect_object_names(&mut scenes, &mut spawns, paths);
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "pub fn scene_named(name: &str) -> Option<&'static dyn Scene> {{").unwrap();
+    writeln!(file, "match name {{").unwrap();
+    for scene in &scenes {
+        writeln!(file, "{:?} => Some(scene::{} as &'static dyn Scene),", scene, scene).unwrap();
+    }
+    writeln!(file, "_ => None,").unwrap();
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "pub fn spawn_named<'a>(builder: &mut SceneBuilder<'a>, name: &str, x: i32, y: i32) -> bool {{").unwrap();
+    writeln!(file, "match name {{").unwrap();
+    for spawn in &spawns {
+        writeln!(file, "{:?} => {{ builder.add_entity(entity::{}(x, y)); }}", spawn, spawn).unwrap();
+    }
+    writeln!(file, "_ => return false,").unwrap();
+    writeln!(file, "}}").unwrap();
+    writeln!(file, "true").unwrap();
+    writeln!(file, "}}").unwrap();
+}
+
+fn collect_object_names(scenes: &mut BTreeSet<String>, spawns: &mut BTreeSet<String>, paths: ReadDir) {
+    for path in paths {
+        let path = path.unwrap().path();
+        if path.is_dir() {
+            collect_object_names(scenes, spawns, fs::read_dir(path).unwrap());
+        } else if path.extension() == Some(&OsStr::new("tmx")) {
+            let tile_grid = TiledTMXSpec::load(&path).unwrap();
+            for object in tile_grid.object_groups.iter().flat_map(|group| &group.objects) {
+                match object.kind.as_str() {
+                    "door" => scenes.extend(object.properties.get("scene").cloned()),
+                    "spawn" => { spawns.insert(object.name.clone()); }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Set this variable while building to see how many walls each map's collision tiles were merged
+/// into.
+const COLLISION_STATS_VAR: &str = "CAT_GAME_COLLISION_STATS";
+
+/// Generates the `collisions` function for a map, which adds the `Wall`s for its solid tiles.
+/// Neighbouring solid tiles are merged into as few walls as possible.
+fn write_collisions<W: Write>(file: &mut W, path: &Path, tile_grid: &TiledSpec) {
+    let solid = solid_tiles(tile_grid);
+    let walls = merge_tiles(&solid, tile_grid.width);
+    if env::var_os(COLLISION_STATS_VAR).is_some() {
+        println!(
+            "cargo:warning={}: merged {} collision tiles into {} walls",
+            path.display(),
+            solid.iter().filter(|solid| **solid).count(),
+            walls.len(),
+        );
+    }
+    writeln!(file, "pub fn collisions<'a>(builder: &mut SceneBuilder<'a>) {{").unwrap();
+    writeln!(file, "builder").unwrap();
+    for wall in walls {
+        let x = wall.x * tile_grid.tilewidth;
+        let y = wall.y * tile_grid.tileheight;
+        let width = wall.width * tile_grid.tilewidth;
+        let height = wall.height * tile_grid.tileheight;
+        writeln!(file, ".add_entity(Wall({}, {}, {}, {}))", x, y, width, height).unwrap();
+    }
+    writeln!(file, ";}}").unwrap();
+}
+
+/// Generates the `spawn_objects` function for a map, which adds all the entities that were placed
+/// in the map's object layers. The object's type determines which entity it becomes:
+///
+/// *   `door`: a `Door` named by the object, leading to the `scene` property, with the player
+///     exiting at the `exit_x`/`exit_y` offset
+/// *   `wall`: a `Wall` covering the object
+/// *   `state_pickup`: a `StatePickup` covering the object, which enters the `state` property
+/// *   `spawn`: the entity named by the object (e.g. `character::mystery_man::Intro`), at the
+///     object's position
+///
+/// Any object may also have a `when` property, in which case it is only added while the game is in
+/// that `MainState`.
+fn write_objects<W: Write>(file: &mut W, path: &Path, object_groups: &[TiledObjectGroup]) -> Result<()> {
+    writeln!(file, "pub fn spawn_objects<'a>(builder: &mut SceneBuilder<'a>) {{").unwrap();
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(file, "{{ if crate::hot_reload::spawn_objects(builder, SOURCE) {{ return; }} }}").unwrap();
+    for group in object_groups {
+        for object in &group.objects {
+            let field = |name: &str| format!("objectgroup[{}].object[{}].{}", group.name, object.name, name);
+            let property = |name: &str| object.properties
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Diagnostic::new(path, field(name), format!("a {} must have this property", object.kind)));
+            let int_property = |name: &str| match object.properties.get(name) {
+                Some(value) => value
+                    .parse::<i32>()
+                    .map_err(|_| Diagnostic::new(path, field(name), format!("{:?} is not a whole number", value))),
+                None => Ok(0),
+            };
+            let entity = match object.kind.as_str() {
+                "door" => format!(
+                    "Door({:?}, scene::{}, {}, {}, {}, {}, {}, {})",
+                    object.name,
+                    property("scene")?,
+                    object.x,
+                    object.y,
+                    object.width,
+                    object.height,
+                    int_property("exit_x")?,
+                    int_property("exit_y")?,
+                ),
+                "wall" => format!("Wall({}, {}, {}, {})", object.x, object.y, object.width, object.height),
+                "state_pickup" => format!(
+                    "StatePickup({}, {}, {}, {}, MainState::{})",
+                    object.x,
+                    object.y,
+                    object.width,
+                    object.height,
+                    property("state")?,
+                ),
+                "spawn" => format!("entity::{}({}, {})", object.name, object.x, object.y),
+                kind => return Err(Diagnostic::new(path, field("type"), format!("{:?} is not one of door, wall, state_pickup or spawn", kind))),
+            };
+            if let Some(state) = object.properties.get("when") {
+                writeln!(file, "if builder.get_resource::<State>().is(MainState::{}) {{ builder.add_entity({}); }}", state, entity).unwrap();
+            } else {
+                writeln!(file, "builder.add_entity({});", entity).unwrap();
+            }
+        }
+    }
+    writeln!(file, "}}").unwrap();
+    Ok(())
+}
@@ -1,39 +1,109 @@
 use std::{
+    collections::BTreeMap,
     fs::{self, ReadDir},
     io::Write,
     ffi::OsStr,
+    path::{Path, PathBuf},
 };
 
-use toml::from_str;
-
 use super::schema::*;
 
-pub fn write_tile_sets<'a, W: Write>(file: &mut W, paths: ReadDir) {
-    for path in paths {
-        let path = path.unwrap().path();
-        if path.is_dir() {
-            writeln!(file, "pub mod {} {{", path.file_name().unwrap().to_str().unwrap().to_owned().to_lowercase()).unwrap();
-            writeln!(file, "use super::{{image, TileSet, Point, Dimen}};").unwrap();
-            let sub_paths = fs::read_dir(path).unwrap();
-            write_tile_sets(file, sub_paths);
-            writeln!(file, "}}").unwrap();
-        } else if path.extension() == Some(&OsStr::new("toml")) {
-            let name = path.file_stem().unwrap();
-            let const_name = name.to_str().unwrap().to_owned().to_uppercase();
-            let toml_str = fs::read_to_string(&path).unwrap();
-            let tile_set: TileSetSpec = from_str(&toml_str).unwrap();
+/// Writes a `TileSet` for every tile set used by the Tiled maps in the `paths`, whether embedded in
+/// the map or in an external TSX file, along with a `TileAnimation` for each of its animated tiles
+/// and `TileProperties` for each of its tiles that have custom properties.
+/// Tile sets are identified by name, so two maps may share a tile set, but only if they agree on
+/// what it is.
+pub fn write_tiled_tile_sets<'a, W: Write>(file: &mut W, images_dir: &Path, paths: ReadDir) {
+    let mut tile_sets = BTreeMap::new();
+    collect_tiled_tile_sets(&mut tile_sets, paths);
+    let images_dir = images_dir.canonicalize().unwrap();
+    writeln!(file, "#[cfg(feature = \"hot-reload\")]").unwrap();
+    writeln!(
+        file,
+        "pub const TILED: &[(&str, &TileSet)] = &[{}];",
+        tile_sets
+            .keys()
+            .map(|const_name| format!("({:?}, &{})", const_name, const_name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ).unwrap();
+    for (const_name, (tile_set, map)) in tile_sets {
+        let image_path = tile_set.image
+            .strip_prefix(&images_dir)
+            .expect(&format!(
+                "Image {} of tile set {} in {} is not in the image directory",
+                tile_set.image.display(),
+                tile_set.name,
+                map.display(),
+            ));
+        let mut image = image_path
+            .parent()
+            .unwrap()
+            .iter()
+            .map(|dir| dir.to_str().unwrap().to_lowercase())
+            .collect::<Vec<_>>();
+        image.push(image_path.file_stem().unwrap().to_str().unwrap().to_uppercase());
+        writeln!(
+            file,
+            "pub const {}: TileSet = TileSet::new(&image::{}, {}, {}, {}, {}, {});",
+            const_name,
+            image.join("::"),
+            tile_set.tilecount,
+            tile_set.columns,
+            Dimen { width: tile_set.tilewidth, height: tile_set.tileheight },
+            Point { x: tile_set.margin, y: tile_set.margin },
+            Dimen { width: tile_set.spacing, height: tile_set.spacing },
+        ).unwrap();
+        for (id, frames) in &tile_set.animations {
+            writeln!(
+                file,
+                "pub const {}: TileAnimation = TileAnimation::new(&[{}], {});",
+                tile_set.animation_const_name(*id),
+                frames
+                    .iter()
+                    .map(|frame| format!("TileFrame::new({}, {})", frame.tileid, frame.duration))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                frames.iter().map(|frame| frame.duration).sum::<u32>(),
+            ).unwrap();
+        }
+        for (id, properties) in &tile_set.properties {
             writeln!(
-                file, 
-                "pub const {}: TileSet = TileSet::new(&image::{}, {}, {}, {}, {}, {});", 
-                const_name, 
-                tile_set.image,
-                tile_set.count,
-                tile_set.per_row,
-                tile_set.size,
-                tile_set.origin,
-                tile_set.spacing,
+                file,
+                "pub const {}: TileProperties = TileProperties::new(&[{}]);",
+                tile_set.properties_const_name(*id),
+                properties
+                    .iter()
+                    .map(|(name, value)| format!("({:?}, {:?})", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", "),
             ).unwrap();
         }
     }
 }
 
+fn collect_tiled_tile_sets(tile_sets: &mut BTreeMap<String, (TiledTileset, PathBuf)>, paths: ReadDir) {
+    for path in paths {
+        let path = path.unwrap().path();
+        if path.is_dir() {
+            collect_tiled_tile_sets(tile_sets, fs::read_dir(path).unwrap());
+        } else if path.extension() == Some(&OsStr::new("tmx")) {
+            let tile_grid = TiledTMXSpec::load(&path).unwrap();
+            for tile_set in tile_grid.tilesets {
+                let const_name = tile_set.const_name();
+                if let Some((existing, map)) = tile_sets.get(&const_name) {
+                    if !existing.same_as(&tile_set) {
+                        panic!(
+                            "Tile set {} is defined differently in {} and {}",
+                            tile_set.name,
+                            map.display(),
+                            path.display(),
+                        );
+                    }
+                    continue;
+                }
+                tile_sets.insert(const_name, (tile_set, path.clone()));
+            }
+        }
+    }
+}
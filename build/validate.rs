@@ -0,0 +1,498 @@
+//! Checks all the assets and the references between them before any code is generated, so that
+//! a mistake is reported against the file that contains it, rather than as a panic somewhere in a
+//! generator or as generated code that fails to compile.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::Read,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use inkgen::parse;
+
+use super::schema::*;
+use super::diagnostic::*;
+use super::ink::lint_ink;
+use super::markup::{PAUSE, CONDITION};
+
+/// The largest frame that fits in the portrait space of the dialog box, which must agree with
+/// `PORTRAIT_SIZE` in `drawable/dialog.rs`.
+const PORTRAIT_SIZE: u32 = 96;
+
+/// Runs all the checks, returning everything that was found to be wrong.
+pub fn validate(resources_dir: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let images_dir = resources_dir.join("image");
+
+    let mut images = HashMap::new();
+    collect_images(&mut images, &images_dir, "");
+
+    let mut sprites = HashMap::new();
+    validate_sprites(&mut diagnostics, &mut sprites, &images, &resources_dir.join("sprite"), "");
+    let mut fonts = HashSet::new();
+    validate_fonts(&mut diagnostics, &mut fonts, &resources_dir.join("font"));
+
+    let mut tile_sets = HashMap::new();
+    validate_tile_grids(&mut diagnostics, &mut tile_sets, &images_dir, &resources_dir.join("tile_grid"));
+
+    let rules = validate_rules(&mut diagnostics, &fonts, &resources_dir.join("dialog").join("rules.toml"));
+    let speakers = validate_speakers(&mut diagnostics, &sprites, &resources_dir.join("dialog").join("speakers.toml"));
+    validate_inks(&mut diagnostics, &rules, &speakers, &resources_dir.join("dialog"));
+    diagnostics
+}
+
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .map(|paths| paths.filter_map(|path| path.ok()).map(|path| path.path()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// Collects the name of every `Image` constant that will be generated, along with the path to the
+/// image file.
+fn collect_images(images: &mut HashMap<String, PathBuf>, dir: &Path, prefix: &str) {
+    for path in files(dir) {
+        if path.is_dir() {
+            let module = path.file_name().unwrap().to_str().unwrap().to_lowercase();
+            collect_images(images, &path, &format!("{}{}::", prefix, module));
+        } else if path.extension() != Some(&OsStr::new("rs")) {
+            let const_name = path.file_stem().unwrap().to_str().unwrap().to_uppercase();
+            images.insert(format!("{}{}", prefix, const_name), path);
+        }
+    }
+}
+
+/// Reads the width and height of a PNG image from its header, or `None` if the file is not a PNG.
+fn png_size(path: &Path) -> Option<Dimen> {
+    let mut header = [0u8; 24];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[0..8] != b"\x89PNG\r\n\x1a\n" || &header[12..16] != b"IHDR" {
+        return None;
+    }
+    let read_u32 = |bytes: &[u8]| (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32;
+    Some(Dimen {
+        width: read_u32(&header[16..20]),
+        height: read_u32(&header[20..24]),
+    })
+}
+
+/// Checks the sprites, collecting the name of each one along with its frames.
+fn validate_sprites(
+    diagnostics: &mut Vec<Diagnostic>,
+    sprites: &mut HashMap<String, Vec<[u32; 4]>>,
+    images: &HashMap<String, PathBuf>,
+    dir: &Path,
+    prefix: &str,
+) {
+    for path in files(dir) {
+        if path.is_dir() {
+            let module = path.file_name().unwrap().to_str().unwrap().to_lowercase();
+            validate_sprites(diagnostics, sprites, images, &path, &format!("{}{}::", prefix, module));
+        } else if path.extension() == Some(&OsStr::new("toml")) {
+            let sprite = match SpriteSpec::load(&path) {
+                Ok(sprite) => sprite,
+                Err(diagnostic) => { diagnostics.push(diagnostic); continue; }
+            };
+            let image = match images.get(&sprite.image) {
+                Some(image) => image,
+                None => {
+                    diagnostics.push(Diagnostic::new(&path, "image", format!("there is no image named {:?}", sprite.image)));
+                    continue;
+                }
+            };
+            let actual = png_size(image);
+            if let (Some(declared), Some(actual)) = (sprite.dimensions, actual) {
+                if declared.width != actual.width || declared.height != actual.height {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        "dimensions",
+                        format!(
+                            "declared as {}x{}, but {} is {}x{}",
+                            declared.width,
+                            declared.height,
+                            image.display(),
+                            actual.width,
+                            actual.height,
+                        ),
+                    ));
+                }
+            }
+            if let Some(dimensions) = sprite.dimensions.or(actual) {
+                for (i, [x, y, w, h]) in sprite.frames.iter().enumerate() {
+                    if x + w > dimensions.width || y + h > dimensions.height {
+                        diagnostics.push(Diagnostic::new(
+                            &path,
+                            format!("frames[{}]", i),
+                            format!(
+                                "[{}, {}, {}, {}] does not fit inside the {}x{} image",
+                                x, y, w, h,
+                                dimensions.width,
+                                dimensions.height,
+                            ),
+                        ));
+                    }
+                }
+            }
+            let count = sprite.frames.len() as u32;
+            for (name, animation) in &sprite.animations {
+                if !is_identifier(name) {
+                    diagnostics.push(Diagnostic::new(&path, format!("animations.{}", name), "the name of an animation must be a valid identifier"));
+                }
+                if animation.end > count {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("animations.{}.frames", name),
+                        format!("frames {}..{} are out of range, there are only {} frames", animation.start, animation.end, count),
+                    ));
+                }
+                if animation.idle >= count {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("animations.{}.idle", name),
+                        format!("frame {} is out of range, there are only {} frames", animation.idle, count),
+                    ));
+                }
+            }
+            let const_name = path.file_stem().unwrap().to_str().unwrap().to_uppercase();
+            sprites.insert(format!("{}{}", prefix, const_name), sprite.frames);
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|ch: char| ch.is_ascii_digit())
+        && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn validate_fonts(diagnostics: &mut Vec<Diagnostic>, fonts: &mut HashSet<(String, String, u32)>, dir: &Path) {
+    for path in files(dir) {
+        if !path.is_dir() && path.extension() == Some(&OsStr::new("toml")) {
+            let fonts: FontSpec = match read_toml(&path) {
+                Ok(fonts) => fonts,
+                Err(diagnostic) => { diagnostics.push(diagnostic); continue; }
+            };
+            for (i, font) in fonts.styles.iter().enumerate() {
+                if !dir.join(&font.file).is_file() {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("styles[{}].file", i),
+                        format!("{:?} does not exist in {}", font.file, dir.display()),
+                    ));
+                }
+                let family = path.file_stem().unwrap().to_str().unwrap();
+                for size in &font.sizes {
+                    fonts.insert((family.to_owned(), font.name.clone(), *size));
+                }
+            }
+        }
+    }
+}
+
+fn validate_tile_grids(
+    diagnostics: &mut Vec<Diagnostic>,
+    tile_sets: &mut HashMap<String, u32>,
+    images_dir: &Path,
+    dir: &Path,
+) {
+    // The Tiled maps define the tile sets, so those are collected before any TOML grid is
+    // checked against them.
+    let mut tomls = vec![];
+    let mut tmxs = vec![];
+    collect_tile_grids(&mut tomls, &mut tmxs, dir);
+
+    let images_dir = images_dir.canonicalize().unwrap();
+    for path in tmxs {
+        let map = match TiledTMXSpec::load(&path) {
+            Ok(map) => map,
+            Err(diagnostic) => { diagnostics.push(diagnostic); continue; }
+        };
+        for tile_set in &map.tilesets {
+            if !tile_set.image.starts_with(&images_dir) {
+                diagnostics.push(Diagnostic::new(
+                    &path,
+                    format!("tileset[{}].image", tile_set.name),
+                    format!("{} is not in {}", tile_set.image.display(), images_dir.display()),
+                ));
+            }
+            tile_sets.insert(tile_set.const_name(), tile_set.tilecount);
+            for (id, frames) in &tile_set.animations {
+                let field = format!("tileset[{}].tile[{}].animation", tile_set.name, id);
+                if *id >= tile_set.tilecount {
+                    diagnostics.push(Diagnostic::new(&path, field.clone(), format!("tile {} is out of range, there are only {} tiles", id, tile_set.tilecount)));
+                }
+                if frames.is_empty() {
+                    diagnostics.push(Diagnostic::new(&path, field.clone(), "an animation must have at least one frame"));
+                }
+                for frame in frames {
+                    if frame.tileid >= tile_set.tilecount {
+                        diagnostics.push(Diagnostic::new(&path, format!("{}.frame.tileid", field), format!("tile {} is out of range, there are only {} tiles", frame.tileid, tile_set.tilecount)));
+                    }
+                }
+                if !frames.is_empty() && frames.iter().all(|frame| frame.duration == 0) {
+                    diagnostics.push(Diagnostic::new(&path, format!("{}.frame.duration", field), "at least one frame must last longer than 0ms"));
+                }
+            }
+            for (id, properties) in &tile_set.properties {
+                let field = format!("tileset[{}].tile[{}].properties", tile_set.name, id);
+                if *id >= tile_set.tilecount {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        field.clone(),
+                        format!("tile {} is out of range, there are only {} tiles", id, tile_set.tilecount),
+                    ));
+                }
+                if let Some(speed) = properties.get("speed") {
+                    if let Err(diagnostic) = parse_number::<f32>(&path, &format!("{}.speed", field), speed) {
+                        diagnostics.push(diagnostic);
+                    }
+                }
+            }
+        }
+        for layer in &map.layers {
+            if layer.tiles.len() != (map.width * map.height) as usize {
+                diagnostics.push(Diagnostic::new(
+                    &path,
+                    format!("layer[{}].data", layer.name),
+                    format!("contains {} tiles, but the map is {}x{}", layer.tiles.len(), map.width, map.height),
+                ));
+            }
+            if layer.name.to_uppercase() == "COLLISIONS" {
+                continue;
+            }
+            match layer.depth {
+                None => diagnostics.push(Diagnostic::new(
+                    &path,
+                    format!("layer[{}].depth", layer.name),
+                    "every layer but the collisions must have a depth to be drawn at",
+                )),
+                Some(depth) => if let Some(other) = map.layers.iter().find(|other| other.depth == Some(depth)).filter(|other| other.name != layer.name) {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("layer[{}].depth", layer.name),
+                        format!("layer {} is already at depth {}", other.name, depth),
+                    ));
+                },
+            }
+            for (i, tile) in layer.tiles.iter().enumerate() {
+                if tile.gid != 0 && !map.tilesets.iter().any(|set| set.contains(tile.gid)) {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("layer[{}].data", layer.name),
+                        format!(
+                            "the tile at ({}, {}) has GID {}, which is not in any tile set",
+                            i as u32 % map.width,
+                            i as u32 / map.width,
+                            tile.gid,
+                        ),
+                    ));
+                }
+            }
+        }
+        for group in &map.object_groups {
+            for object in &group.objects {
+                let required: &[&str] = match object.kind.as_str() {
+                    "door" => &["scene"],
+                    "state_pickup" => &["state"],
+                    "wall" | "spawn" => &[],
+                    kind => {
+                        diagnostics.push(Diagnostic::new(
+                            &path,
+                            format!("objectgroup[{}].object[{}].type", group.name, object.name),
+                            format!("{:?} is not one of door, wall, state_pickup or spawn", kind),
+                        ));
+                        continue;
+                    }
+                };
+                for property in required {
+                    if !object.properties.contains_key(*property) {
+                        diagnostics.push(Diagnostic::new(
+                            &path,
+                            format!("objectgroup[{}].object[{}].{}", group.name, object.name, property),
+                            format!("a {} must have this property", object.kind),
+                        ));
+                    }
+                }
+                for property in &["exit_x", "exit_y"] {
+                    if let Some(value) = object.properties.get(*property) {
+                        let field = format!("objectgroup[{}].object[{}].{}", group.name, object.name, property);
+                        if let Err(diagnostic) = parse_number::<i32>(&path, &field, value) {
+                            diagnostics.push(diagnostic);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for path in tomls {
+        let tile_grid: TileGridSpec = match read_toml(&path) {
+            Ok(tile_grid) => tile_grid,
+            Err(diagnostic) => { diagnostics.push(diagnostic); continue; }
+        };
+        for (i, name) in tile_grid.tile_sets.iter().enumerate() {
+            if !tile_sets.contains_key(name) {
+                diagnostics.push(Diagnostic::new(&path, format!("tile_sets[{}]", i), format!("there is no tile set named {:?}", name)));
+            }
+        }
+        if tile_grid.tiles.len() != (tile_grid.size.width * tile_grid.size.height) as usize {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                "tiles",
+                format!("contains {} tiles, but size is {}x{}", tile_grid.tiles.len(), tile_grid.size.width, tile_grid.size.height),
+            ));
+        }
+        for (i, tile) in tile_grid.tiles.iter().enumerate() {
+            if let Tile::Some(set, index) = tile {
+                match tile_grid.tile_sets.get(*set) {
+                    None => diagnostics.push(Diagnostic::new(
+                        &path,
+                        format!("tiles[{}]", i),
+                        format!("tile set index {} is out of range, there are only {} tile_sets", set, tile_grid.tile_sets.len()),
+                    )),
+                    Some(name) => if let Some(count) = tile_sets.get(name) {
+                        if index >= count {
+                            diagnostics.push(Diagnostic::new(
+                                &path,
+                                format!("tiles[{}]", i),
+                                format!("tile index {} is out of range, {} only has {} tiles", index, name, count),
+                            ));
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn collect_tile_grids(tomls: &mut Vec<PathBuf>, tmxs: &mut Vec<PathBuf>, dir: &Path) {
+    for path in files(dir) {
+        if path.is_dir() {
+            collect_tile_grids(tomls, tmxs, &path);
+        } else if path.extension() == Some(&OsStr::new("toml")) {
+            tomls.push(path);
+        } else if path.extension() == Some(&OsStr::new("tmx")) {
+            tmxs.push(path);
+        }
+    }
+}
+
+/// Checks the text style rules, returning the names of the ones that were declared.
+fn validate_rules(diagnostics: &mut Vec<Diagnostic>, fonts: &HashSet<(String, String, u32)>, path: &Path) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let dialog: DialogSpec = match read_toml(path) {
+        Ok(dialog) => dialog,
+        Err(diagnostic) => { diagnostics.push(diagnostic); return names; }
+    };
+    for rule in &dialog.rules {
+        if rule.name.is_empty() || rule.name.contains(|ch| ch == '<' || ch == '>' || ch == ':') {
+            diagnostics.push(Diagnostic::new(path, format!("rules[{}].name", rule.name), "a rule name may not be empty or contain <, > or :"));
+        }
+        if rule.name == PAUSE {
+            diagnostics.push(Diagnostic::new(path, format!("rules[{}].name", rule.name), "pause is not a rule, but the <pause:N> tag"));
+        }
+        if rule.name == CONDITION {
+            diagnostics.push(Diagnostic::new(path, format!("rules[{}].name", rule.name), "if is not a rule, but the <if:condition> tag"));
+        }
+        if !names.insert(rule.name.clone()) {
+            diagnostics.push(Diagnostic::new(path, format!("rules[{}]", rule.name), "is declared more than once"));
+        }
+        let style = match rule.resolve(path) {
+            Ok(style) => style,
+            Err(diagnostic) => { diagnostics.push(diagnostic); continue; }
+        };
+        // the same defaults as `Attributes::font`
+        if style.family.is_some() || style.style.is_some() || style.size.is_some() {
+            let font = (
+                style.family.unwrap_or_else(|| "default".to_owned()),
+                style.style.unwrap_or_else(|| "regular".to_owned()),
+                style.size.unwrap_or(20),
+            );
+            if !fonts.contains(&font) {
+                diagnostics.push(Diagnostic::new(
+                    path,
+                    format!("rules[{}]", rule.name),
+                    format!("there is no {} {} font in size {}", font.0, font.1, font.2),
+                ));
+            }
+        }
+    }
+    names
+}
+
+/// Checks the speaker registry, returning the expressions of each speaker that was declared.
+fn validate_speakers(
+    diagnostics: &mut Vec<Diagnostic>,
+    sprites: &HashMap<String, Vec<[u32; 4]>>,
+    path: &Path,
+) -> HashMap<String, HashSet<String>> {
+    let mut speakers = HashMap::new();
+    let registry: SpeakersSpec = match read_toml(path) {
+        Ok(registry) => registry,
+        Err(diagnostic) => { diagnostics.push(diagnostic); return speakers; }
+    };
+    for speaker in registry.speakers {
+        let field = format!("speakers[{}]", speaker.name);
+        if speaker.name.is_empty() || speaker.name.contains(|ch| ch == '<' || ch == ':' || ch == '[' || ch == ']') {
+            diagnostics.push(Diagnostic::new(path, format!("{}.name", field), "a speaker name may not be empty or contain <, :, [ or ]"));
+        }
+        if speakers.contains_key(&speaker.name) {
+            diagnostics.push(Diagnostic::new(path, field.clone(), "is declared more than once"));
+        }
+        if !speaker.expressions.contains_key("neutral") {
+            diagnostics.push(Diagnostic::new(path, format!("{}.expressions", field), "there must be a neutral expression"));
+        }
+        match sprites.get(&speaker.portrait) {
+            None => diagnostics.push(Diagnostic::new(
+                path,
+                format!("{}.portrait", field),
+                format!("there is no sprite named {:?}", speaker.portrait),
+            )),
+            Some(frames) => {
+                for (name, frame) in &speaker.expressions {
+                    match frames.get(*frame as usize) {
+                        None => diagnostics.push(Diagnostic::new(
+                            path,
+                            format!("{}.expressions.{}", field, name),
+                            format!("frame {} is out of range, there are only {} frames", frame, frames.len()),
+                        )),
+                        Some([_, _, w, h]) if *w > PORTRAIT_SIZE || *h > PORTRAIT_SIZE => diagnostics.push(Diagnostic::new(
+                            path,
+                            format!("{}.expressions.{}", field, name),
+                            format!("frame {} is {}x{}, but portraits may be at most {}x{}", frame, w, h, PORTRAIT_SIZE, PORTRAIT_SIZE),
+                        )),
+                        Some(..) => {}
+                    }
+                }
+            }
+        }
+        speakers.insert(speaker.name, speaker.expressions.into_iter().map(|(name, _)| name).collect());
+    }
+    speakers
+}
+
+fn validate_inks(
+    diagnostics: &mut Vec<Diagnostic>,
+    rules: &HashSet<String>,
+    speakers: &HashMap<String, HashSet<String>>,
+    dir: &Path,
+) {
+    for path in files(dir) {
+        if path.is_dir() {
+            validate_inks(diagnostics, rules, speakers, &path);
+        } else if path.extension() == Some(&OsStr::new("ink")) {
+            match fs::read_to_string(&path) {
+                Err(error) => diagnostics.push(Diagnostic::new(&path, "", format!("could not be read: {}", error))),
+                Ok(string) => {
+                    diagnostics.extend(lint_ink(&path, &string, rules, speakers));
+                    if let Err(error) = parse(string) {
+                        diagnostics.push(Diagnostic::new(&path, "", format!("is not valid ink: {:?}", error)));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,26 +1,28 @@
 use specs_derive::Component;
-use std::ops::Range;
 use game_engine::prelude::*;
 
-use crate::model::direction::Direction;
+use crate::model::{
+    direction::Direction,
+    sprite_animation::SpriteAnimation,
+};
 
 // NOTE: I feel like `HashMap` has much overhead for something so simple as this. Might be worth
 // looking into `Vec<(Direction, &[Rect])>` implementation.
 
 #[derive(Component, Clone, Debug)]
 pub struct WalkCycle {
-    pub directions: Vec<(Direction, Range<usize>)>,
+    pub directions: Vec<(Direction, SpriteAnimation)>,
 }
 
 impl WalkCycle {
-    pub fn new(directions: impl Iterator<Item = (Direction, Range<usize>)>) -> Self {
+    pub fn new(directions: impl Iterator<Item = (Direction, SpriteAnimation)>) -> Self {
         WalkCycle { directions: directions.collect() }
     }
 
-    pub fn frames(&self, direction: Direction) -> Option<Range<usize>> {
+    pub fn frames(&self, direction: Direction) -> Option<SpriteAnimation> {
         self.directions
             .iter()
-            .fold(None, |best: Option<(Direction, Range<usize>)>, next| {
+            .fold(None, |best: Option<(Direction, SpriteAnimation)>, next| {
                 if let Some(best) = best {
                     if direction.difference(&next.0) < direction.difference(&best.0) {
                         Some(next.clone())
@@ -31,6 +33,6 @@ impl WalkCycle {
                     Some(next.clone())
                 }
             })
-            .map(|(_, rects)| rects)
+            .map(|(_, animation)| animation)
     }
 }
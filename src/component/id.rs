@@ -1,3 +1,4 @@
+use std::str::FromStr;
 use specs_derive::Component;
 use game_engine::prelude::*;
 
@@ -8,3 +9,16 @@ pub enum Id {
     Player,
     MysteryMan,
 }
+
+/// Parses the name of an Id, as the dialogs refer to them.
+impl FromStr for Id {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "Player" => Ok(Id::Player),
+            "MysteryMan" => Ok(Id::MysteryMan),
+            _ => Err(()),
+        }
+    }
+}
@@ -11,6 +11,15 @@ impl Inventory {
         *self.0.get(item).unwrap_or(&0)
     }
 
+    /// How many of the item there are, looking it up by its name, as the dialogs do.
+    pub fn count_named(&self, name: &str) -> u32 {
+        self.0
+            .iter()
+            .filter(|(item, _)| item.name() == name)
+            .map(|(_, count)| *count)
+            .sum()
+    }
+
     pub fn has(&self, item: &Item, count: u32) -> bool {
         self.count(item) >= count
     }
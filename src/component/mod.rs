@@ -8,6 +8,7 @@ pub mod door;
 pub mod id;
 pub mod inventory;
 pub mod position;
+pub mod speech_bubble;
 pub mod state_target;
 pub mod velocity;
 
@@ -21,6 +22,7 @@ pub fn register<'a, 'b>(game: Game<'a, 'b>) -> Game<'a, 'b> {
         .register_component::<door::TargetScene>()
         .register_component::<door::DoorExit>()
         .register_component::<state_target::StateTarget>()
+        .register_component::<speech_bubble::SpeechBubble>()
         .pipe(graphics::register)
         .pipe(marker::register)
         .pipe(behavior::register)
@@ -0,0 +1,28 @@
+use specs_derive::Component;
+use game_engine::prelude::*;
+use crate::model::pretty_string::PrettyString;
+
+/// How long a bubble stays up, before counting the time it takes to read it.
+const BASE_FRAMES: u32 = 90;
+const FRAMES_PER_GLYPH: u32 = 4;
+
+/// A short line that an entity says out loud, which is shown over its head for a while. Unlike the
+/// dialog, it does not stop anyone from moving or take any input.
+#[derive(Component, Clone, Debug)]
+pub struct SpeechBubble {
+    pub text: PrettyString,
+    frames_left: u32,
+}
+
+impl SpeechBubble {
+    pub fn new(text: PrettyString) -> Self {
+        let frames_left = BASE_FRAMES + FRAMES_PER_GLYPH * text.len() as u32;
+        SpeechBubble { text, frames_left }
+    }
+
+    /// Counts down a frame, returning whether the bubble should still be shown.
+    pub fn tick(&mut self) -> bool {
+        self.frames_left = self.frames_left.saturating_sub(1);
+        self.frames_left != 0
+    }
+}
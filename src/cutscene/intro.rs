@@ -1,21 +1,16 @@
 use game_engine::prelude::*;
-use serde::Deserialize;
 use crate::constant::TILE_SIZE;
 use crate::model::cutscene::{Cutscene, StandardCutscene, Step::*, Step};
+use crate::model::dialog_event::DialogEvent;
 use crate::component::id::Id;
 use crate::dialog::intro as dialog;
 use crate::resource::state::MainState;
 
-#[derive(Deserialize, PartialEq)]
-enum EnterAlleyEvent {
-    ComeOut,
-}
-
-const CUTSCENE: [Step<EnterAlleyEvent>; 10] = [
+const CUTSCENE: [Step; 10] = [
     Move(Id::Player, &[Point::new(TILE_SIZE as f32 * 24f32, TILE_SIZE as f32 * 11f32)]),
     AwaitMoveEnd(Id::Player),
     StartDialog(dialog::enter_alley::story),
-    AwaitDialogEvent(EnterAlleyEvent::ComeOut),
+    AwaitDialogEvent(DialogEvent::ComeOut),
     Move(Id::MysteryMan, &[Point::new(TILE_SIZE as f32 * 19f32, TILE_SIZE as f32 * 11f32)]),
     AwaitDialogEnd,
     Move(Id::Player, &[Point::new(TILE_SIZE as f32 * 25f32, TILE_SIZE as f32 * 11f32)]),
@@ -0,0 +1,113 @@
+use std::any::Any;
+use std::sync::Arc;
+use game_engine::prelude::*;
+
+use crate::model::pretty_string::{Attributes, PrettyString};
+use crate::font::default::REGULAR_20 as DEFAULT_FONT;
+use crate::resource::dialog::HistoryEntry;
+use crate::text_layout::{self, TextLayoutCache};
+
+#[derive(Default, Debug)]
+pub struct BacklogDrawable {
+    pub open: bool,
+    pub scroll: usize,
+    pub conversations: Arc<Vec<Vec<HistoryEntry>>>,
+    text_layouts: TextLayoutCache,
+}
+
+impl BacklogDrawable {
+    pub fn boxed() -> Box<dyn Drawable> {
+        Box::new(Self::default())
+    }
+}
+
+const MARGIN: i32 = 32;
+const H_PADDING: i32 = 16;
+const V_PADDING: i32 = 16;
+const ENTRY_SPACING: i32 = 8;
+const CONVERSATION_SPACING: i32 = 24;
+const CHOICE_COLOR: u32 = 0x555050ff;
+
+/// The text of an entry, starting with the speaker's name or the choice marker.
+fn entry_text(entry: &HistoryEntry) -> PrettyString {
+    let (prefix, message) = match entry {
+        HistoryEntry::Line(message) => (
+            message.speaker().as_ref().map(|speaker| (
+                format!("{}: ", speaker),
+                Attributes { style: Some("bold"), ..Attributes::default() },
+            )),
+            message,
+        ),
+        HistoryEntry::Choice(message) => (
+            Some((String::from("> "), Attributes { color: Some(CHOICE_COLOR), ..Attributes::default() })),
+            message,
+        ),
+    };
+    PrettyString::from_segments(prefix.into_iter().chain(message.message().0.iter().cloned()).collect())
+}
+
+impl Drawable for BacklogDrawable {
+    fn depth(&self) -> i32 {
+        ::std::i32::MAX - 1
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) -> game_engine::Result<()> {
+        if !self.open { return Ok(()); }
+
+        let size = canvas.size();
+        canvas.set_transform(Rect::from(Point::default(), size), Rect::from(Point::default(), size));
+        let backlog_box = Rect::new(MARGIN, MARGIN, size.width - 2 * MARGIN as u32, size.height - 2 * MARGIN as u32);
+        canvas.set_color(Color::WHITE);
+        canvas.draw_rect_filled(backlog_box)?;
+        canvas.set_color(Color::BLACK);
+        canvas.draw_rect(backlog_box)?;
+
+        canvas.set_font(DEFAULT_FONT);
+        let max_width = backlog_box.width - 2 * H_PADDING as u32;
+        let top = backlog_box.y + V_PADDING;
+
+        // the most recent entries are at the bottom, so lay them out from the bottom up, skipping
+        // the ones that have been scrolled past
+        let mut y = backlog_box.y + backlog_box.height as i32 - V_PADDING;
+        let mut skipped = 0;
+        'done: for (i, conversation) in self.conversations.iter().enumerate().rev() {
+            for (j, entry) in conversation.iter().enumerate().rev() {
+                if skipped < self.scroll {
+                    skipped += 1;
+                    continue;
+                }
+                let text = self.text_layouts.layout(canvas, &entry_text(entry), max_width)?;
+                for line in text.lines().iter().rev() {
+                    y -= line.spacing;
+                    if y < top {
+                        break 'done;
+                    }
+                    for run in text.runs(line.glyphs.clone(), None) {
+                        canvas.set_font(text_layout::font(&run.attributes));
+                        let point = Point::new(backlog_box.x + H_PADDING + run.position.x, y + run.position.y - line.y);
+                        if let Some(background) = run.attributes.background {
+                            canvas.set_color(Color::from(background));
+                            canvas.draw_rect_filled(Rect::new(point.x, point.y, run.width, run.height))?;
+                        }
+                        canvas.set_color(run.attributes.color.map(Color::from).unwrap_or(Color::BLACK));
+                        if run.attributes.is_underlined() {
+                            canvas.draw_rect_filled(Rect::new(point.x, point.y + canvas.font_ascent()? + 2, run.width, 1))?;
+                        }
+                        canvas.draw_text(point, run.text)?;
+                    }
+                }
+                if j != 0 {
+                    y -= ENTRY_SPACING;
+                }
+            }
+            if i != 0 && skipped >= self.scroll {
+                y -= CONVERSATION_SPACING;
+            }
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,86 @@
+use std::any::Any;
+use game_engine::prelude::*;
+
+use crate::font::default::REGULAR_20 as DEFAULT_FONT;
+
+#[derive(Default, Debug)]
+pub struct ControlsDrawable {
+    pub open: bool,
+    /// The name of each control, and the name of the key or button it is bound to.
+    pub controls: Vec<(&'static str, String)>,
+    pub selected: usize,
+    pub capturing: bool,
+    pub message: Option<String>,
+}
+
+impl ControlsDrawable {
+    pub fn boxed() -> Box<dyn Drawable> {
+        Box::new(Self::default())
+    }
+}
+
+const MARGIN: i32 = 32;
+const H_PADDING: i32 = 16;
+const V_PADDING: i32 = 16;
+/// How far from the left of the box the bindings are lined up.
+const BINDING_X: i32 = 240;
+const TITLE: &str = "Controls";
+const CAPTURING: &str = "Press a key or button...";
+const HINT: &str = "Pick a control to change it, or press Escape to close";
+const SELECTED_COLOR: u32 = 0xe8e8e8ff;
+const HINT_COLOR: u32 = 0x555050ff;
+
+impl Drawable for ControlsDrawable {
+    fn depth(&self) -> i32 {
+        ::std::i32::MAX - 1
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) -> game_engine::Result<()> {
+        if !self.open { return Ok(()); }
+
+        let size = canvas.size();
+        canvas.set_transform(Rect::from(Point::default(), size), Rect::from(Point::default(), size));
+        let controls_box = Rect::new(MARGIN, MARGIN, size.width - 2 * MARGIN as u32, size.height - 2 * MARGIN as u32);
+        canvas.set_color(Color::WHITE);
+        canvas.draw_rect_filled(controls_box)?;
+        canvas.set_color(Color::BLACK);
+        canvas.draw_rect(controls_box)?;
+
+        canvas.set_font(DEFAULT_FONT);
+        let line_spacing = canvas.line_spacing()?;
+        let x = controls_box.x + H_PADDING;
+        let mut y = controls_box.y + V_PADDING;
+        canvas.draw_text(Point::new(x, y), String::from(TITLE))?;
+        y += 2 * line_spacing;
+
+        for (i, (name, binding)) in self.controls.iter().enumerate() {
+            if i == self.selected {
+                canvas.set_color(Color::from(SELECTED_COLOR));
+                canvas.draw_rect_filled(Rect::new(x - H_PADDING / 2, y, controls_box.width - H_PADDING as u32, line_spacing as u32))?;
+            }
+            canvas.set_color(Color::BLACK);
+            canvas.draw_text(Point::new(x, y), String::from(*name))?;
+            let binding = if i == self.selected && self.capturing {
+                String::from(CAPTURING)
+            } else {
+                binding.clone()
+            };
+            canvas.draw_text(Point::new(x + BINDING_X, y), binding)?;
+            y += line_spacing;
+        }
+
+        // the hint sits at the bottom of the box, with whatever happened last just above it
+        canvas.set_color(Color::from(HINT_COLOR));
+        let bottom = controls_box.y + controls_box.height as i32 - V_PADDING - line_spacing;
+        canvas.draw_text(Point::new(x, bottom), String::from(HINT))?;
+        if let Some(message) = &self.message {
+            canvas.set_color(Color::BLACK);
+            canvas.draw_text(Point::new(x, bottom - line_spacing), message.clone())?;
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -1,60 +1,133 @@
 use std::any::Any;
+use std::f32::consts::PI;
 use game_engine::prelude::*;
 use inkgen::runtime::Paragraph;
 
 use crate::model::{
     message::Message,
     pretty_string::Attributes,
+    speaker::Speaker,
 };
 use crate::font::default::REGULAR_20 as DEFAULT_FONT;
+use crate::resource::dialog::DialogLayout;
+use crate::text_layout::{self, TextLayoutCache};
 
 #[derive(Default, Debug)]
 pub struct DialogDrawable {
     pub index: Option<usize>,
     pub paragraph: Option<Paragraph>,
+    /// The indices of the choices that are shown.
+    pub choices: Vec<usize>,
     pub selection: usize,
+    pub layout: DialogLayout,
+    pub page: usize,
+    /// Counts up every frame, to animate the text effects.
+    pub frame: u32,
+    pub auto: bool,
+    pub fast_forward: bool,
+    /// How much time is left to pick a choice in, from 1 down to 0, if the choices are timed.
+    pub timeout: Option<f32>,
+    /// The frame that each glyph of the current paragraph was shown on.
+    revealed: Vec<u32>,
+    text_layouts: TextLayoutCache,
 }
 
 impl DialogDrawable {
     pub fn boxed() -> Box<dyn Drawable> {
         Box::new(Self::default())
     }
-}
 
-const BOX_HEIGHT: u32 = 128;
-const H_PADDING: i32 = 16;
-const V_PADDING: i32 = 16;
+    /// Starts tracking when the glyphs of a new paragraph are shown.
+    pub fn clear_revealed(&mut self) {
+        self.revealed.clear();
+    }
+
+    /// Records that the first `count` glyphs are now shown.
+    pub fn reveal(&mut self, count: usize) {
+        while self.revealed.len() < count {
+            self.revealed.push(self.frame);
+        }
+    }
+
+    /// How far a glyph is moved by the shake and wave effects.
+    fn offset(&self, attributes: &Attributes, index: usize) -> (i32, i32) {
+        let mut offset = (0, 0);
+        if let Some(shake) = attributes.shake {
+            // changes every few frames, otherwise it is too fast to see
+            let seed = (self.frame / SHAKE_FRAMES)
+                .wrapping_mul(31)
+                .wrapping_add(index as u32)
+                .wrapping_mul(2654435761);
+            let range = 2 * shake + 1;
+            offset.0 += ((seed >> 8) % range) as i32 - shake as i32;
+            offset.1 += ((seed >> 16) % range) as i32 - shake as i32;
+        }
+        if let Some(wave) = attributes.wave {
+            let phase = self.frame as f32 / WAVE_FRAMES + index as f32 / WAVE_LENGTH;
+            offset.1 += (wave as f32 * (phase * 2f32 * PI).sin()).round() as i32;
+        }
+        offset
+    }
 
-#[derive(Clone, Debug)]
-struct Segment {
-    text: String,
-    attributes: Attributes,
-    size: Dimen,
-    ascent: i32,
-    min_y: i32,
-    max_y: i32,
+    /// How opaque a glyph is as it fades in, out of 0xff.
+    fn alpha(&self, attributes: &Attributes, index: usize) -> u32 {
+        match attributes.fade {
+            Some(fade) if fade > 0 => {
+                let revealed = self.revealed.get(index).cloned().unwrap_or(self.frame);
+                u32::min(0xff, self.frame.wrapping_sub(revealed).saturating_mul(0xff) / fade)
+            }
+            _ => 0xff,
+        }
+    }
 }
 
-#[derive(Clone, Default, Debug)]
-struct Line {
-    height: u32,
-    width: u32,
-    spacing: i32,
-    segments: Vec<Segment>,
+/// Multiplies the alpha of a colour, so it can be faded.
+fn with_alpha(color: u32, alpha: u32) -> Color {
+    Color::from(color & 0xffffff00 | (color & 0xff) * alpha / 0xff)
 }
 
-// This will be ok because there should only ever be ONE DialogDrawable at one time, and even if
-// there are multiple of them, Drawables are all handled sequentially, so they should not cause
-// simultaneous access here.
-static mut CALCULATED_LINES: Option<Vec<Line>> = None;
-static mut PREVIOUS_MESSAGE: Option<Paragraph> = None;
+/// Draws text with its outline and colour.
+fn draw_styled_text(canvas: &mut dyn Canvas, point: Point, text: String, attributes: &Attributes, alpha: u32) -> game_engine::Result<()> {
+    if let Some(outline) = attributes.outline {
+        canvas.set_color(with_alpha(outline, alpha));
+        for &(dx, dy) in &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+            canvas.draw_text(Point::new(point.x + dx, point.y + dy), text.clone())?;
+        }
+    }
+    canvas.set_color(with_alpha(attributes.color.unwrap_or(TEXT_COLOR), alpha));
+    canvas.draw_text(point, text)
+}
+
+const BOX_HEIGHT: u32 = 128;
+const H_PADDING: i32 = 16;
+const V_PADDING: i32 = 16;
+const SELECTED_CHOICE_COLOR: u32 = 0xe8e8e8ff;
+/// The space beside the text for the speaker's portrait.
+const PORTRAIT_SIZE: i32 = 96;
+const TEXT_COLOR: u32 = 0x000000ff;
+/// How many frames a shaking glyph stays in one place.
+const SHAKE_FRAMES: u32 = 3;
+/// How many frames it takes a wave to go up and down.
+const WAVE_FRAMES: f32 = 60f32;
+/// How many glyphs long a wave is.
+const WAVE_LENGTH: f32 = 8f32;
+/// The size of the arrow that shows there is another page.
+const MORE_SIZE: i32 = 6;
+/// How many frames the arrow that shows there is another page blinks on and off for.
+const MORE_BLINK_FRAMES: u32 = 30;
+const AUTO_LABEL: &str = "Auto";
+const FAST_FORWARD_LABEL: &str = "Fast forward";
+const MODE_COLOR: u32 = 0x555050ff;
+const TIMEOUT_BAR_HEIGHT: u32 = 4;
+const TIMEOUT_COLOR: u32 = 0xc03030ff;
 
 impl Drawable for DialogDrawable {
     fn depth(&self) -> i32 {
-        ::std::i32::MAX - 1
+        ::std::i32::MAX - 2
     }
 
     fn render(&self, canvas: &mut dyn Canvas) -> game_engine::Result<()> {
+        self.layout.clear();
         if let Some(paragraph) = &self.paragraph {
             let message = Message::from(paragraph.text());
 
@@ -63,10 +136,34 @@ impl Drawable for DialogDrawable {
             canvas.set_transform(Rect::from(Point::default(), size), Rect::from(Point::default(), size));
             canvas.set_color(Color::WHITE);
             canvas.set_font(DEFAULT_FONT);
-            canvas.draw_rect_filled(Rect::new(0, (size.height - BOX_HEIGHT) as i32, size.width, BOX_HEIGHT))?;
+            let dialog_box = Rect::new(0, (size.height - BOX_HEIGHT) as i32, size.width, BOX_HEIGHT);
+            self.layout.set_dialog_box(dialog_box);
+            canvas.draw_rect_filled(dialog_box)?;
             canvas.set_color(Color::BLACK);
             canvas.draw_rect(Rect::new(0, (size.height - BOX_HEIGHT) as i32, size.width, 1))?;
 
+            // show the mode that is moving the dialog along, just above the corner of the box
+            let mode = if self.fast_forward {
+                Some(FAST_FORWARD_LABEL)
+            } else if self.auto {
+                Some(AUTO_LABEL)
+            } else {
+                None
+            };
+            if let Some(label) = mode {
+                let Dimen { width, height } = canvas.measure_text(String::from(label))?;
+                let label_box = Rect::new(
+                    size.width as i32 - width as i32 - 2 * H_PADDING,
+                    dialog_box.y - height as i32 - V_PADDING,
+                    width + 2 * H_PADDING as u32,
+                    height + V_PADDING as u32,
+                );
+                canvas.set_color(Color::WHITE);
+                canvas.draw_rect_filled(label_box)?;
+                canvas.set_color(Color::from(MODE_COLOR));
+                canvas.draw_text(Point::new(label_box.x + H_PADDING, label_box.y + V_PADDING / 2), String::from(label))?;
+            }
+
             if let Some(speaker) = message.speaker().to_owned() {
                 let Dimen { width, height } = canvas.measure_text(speaker.clone())?;
                 canvas.set_color(Color::WHITE);
@@ -82,186 +179,130 @@ impl Drawable for DialogDrawable {
                 canvas.draw_text(Point::new(speaker_box.x + H_PADDING, speaker_box.y + V_PADDING), speaker)?;
             }
 
-            let max_width = size.width - 2 * H_PADDING as u32;
-
-            // draw the text segments
-            let segments = message.message().clone().0;
+            // draw the speaker's portrait, and move the text over to make room for it
+            let portrait = message.speaker()
+                .as_ref()
+                .and_then(|speaker| Speaker::find(speaker))
+                .map(|speaker| (speaker.portrait, speaker.frame(message.expression().as_ref().map(String::as_str))));
+            let text_x = if let Some((sprite, frame)) = portrait {
+                canvas.draw_sprite(Point::new(H_PADDING, dialog_box.y + V_PADDING), frame, sprite)?;
+                2 * H_PADDING + PORTRAIT_SIZE
+            } else {
+                H_PADDING
+            };
+            let max_width = size.width - text_x as u32 - H_PADDING as u32;
 
-            unsafe {
-                if CALCULATED_LINES.is_none() || PREVIOUS_MESSAGE.as_ref() != Some(paragraph) {
-                    PREVIOUS_MESSAGE = Some(paragraph.clone());
-                    CALCULATED_LINES = Some(segments
+            // draw the text, one page of it at a time
+            let text = self.text_layouts.layout(canvas, message.message(), max_width)?;
+            let pages = text.pages(BOX_HEIGHT as i32 - 2 * V_PADDING);
+            self.layout.set_pages(&paragraph.text(), pages.iter().map(|page| page.end - page.start).collect());
+            let page = pages.get(self.page).cloned().unwrap_or_else(|| pages[pages.len() - 1].clone());
+            let is_last_page = page.end == text.len();
+            let origin = Point::new(text_x, dialog_box.y + V_PADDING - text.line_y(page.start));
+            for run in text.runs(page, self.index) {
+                let point = Point::new(origin.x + run.position.x, origin.y + run.position.y);
+                canvas.set_font(text_layout::font(&run.attributes));
+                if let Some(background) = run.attributes.background {
+                    canvas.set_color(Color::from(background));
+                    canvas.draw_rect_filled(Rect::new(point.x, point.y, run.width, run.height))?;
+                }
+                if run.attributes.is_underlined() {
+                    canvas.set_color(Color::from(run.attributes.color.unwrap_or(TEXT_COLOR)));
+                    canvas.draw_rect_filled(Rect::new(point.x, point.y + canvas.font_ascent()? + 2, run.width, 1))?;
+                }
+                if run.attributes.has_effects() {
+                    // each glyph moves and fades on its own, so they are drawn one by one
+                    for index in run.glyphs.clone() {
+                        let glyph = &text.glyphs()[index];
+                        let (dx, dy) = self.offset(&run.attributes, index);
+                        let point = Point::new(origin.x + glyph.position.x + dx, origin.y + glyph.position.y + dy);
+                        draw_styled_text(canvas, point, glyph.text.clone(), &run.attributes, self.alpha(&run.attributes, index))?;
+                    }
+                } else {
+                    draw_styled_text(canvas, point, run.text, &run.attributes, 0xff)?;
+                }
+            }
+            if self.index.is_none() && !is_last_page && self.frame / MORE_BLINK_FRAMES % 2 == 0 {
+                // a little arrow in the corner to show that there is more to read
+                canvas.set_color(Color::BLACK);
+                let corner = Point::new(size.width as i32 - H_PADDING, size.height as i32 - V_PADDING);
+                for row in 0..MORE_SIZE {
+                    canvas.draw_rect_filled(Rect::new(
+                        corner.x - 2 * MORE_SIZE + row,
+                        corner.y - MORE_SIZE + row,
+                        2 * (MORE_SIZE - row) as u32,
+                        1,
+                    ))?;
+                }
+            }
+            if self.index.is_none() && is_last_page {
+                if let Some(choices) = paragraph.choices() {
+                    canvas.set_font(DEFAULT_FONT);
+                    let line_spacing = canvas.line_spacing()?;
+                    let strings: Vec<_> = self.choices
                         .iter()
-                        .flat_map(|(text, attributes)| {
-                            let lines: Vec<_> = text.split("\n").collect();
-                            let len = lines.len();
-                            lines
-                                .iter()
-                                .enumerate()
-                                .map(move |(i, line)| (line.to_string(), attributes, i != len - 1))
-                                .collect::<Vec<_>>()
-                        })
-                        .fold(Ok(vec![Line::default()]), |lines: game_engine::Result<Vec<Line>>, (text, attributes, newline)| {
-                            let mut lines = lines?;
-                            if let Some(font) = attributes.font {
-                                canvas.set_font(*font);
-                            } else {
-                                canvas.set_font(DEFAULT_FONT);
-                            }
-                            let Dimen { width, height } = canvas.measure_text(text.to_owned())?;
-                            if lines.last().unwrap().width + width > max_width {
-                                let len = text.len();
-                                // TODO: could probably binary search here if it's too slow
-                                let mut start = 0;
-                                let mut end = 0;
-                                loop {
-                                    let mut word_len = 0;
-                                    while let Some(ch) = text.chars().nth(end + word_len) {
-                                        if ch.is_whitespace() {
-                                            if word_len == 0 {
-                                                end += 1;
-                                            } else {
-                                                break;
-                                            }
-                                        } else {
-                                            word_len += 1;
-                                        }
-                                    }
-                                    let Dimen { width, height } = canvas.measure_text(text.chars().skip(start).take(end + word_len - start).collect())?;
-                                    if lines.last().unwrap().width + width <= max_width {
-                                        end += word_len;
-                                        if end < len - 1 {
-                                            continue;
-                                        }
-                                    }
-                                    let current_line = lines.last_mut().unwrap();
-                                    let (min_y, max_y) = text.chars()
-                                        .skip(start)
-                                        .take(end - start)
-                                        .filter_map(|ch| canvas.glyph_metrics(ch).ok().and_then(|x| x))
-                                        .fold((None, None), |(min_y, max_y), metrics| (
-                                            min_y.map(|min_y| i32::min(min_y, metrics.miny)).or(Some(metrics.miny)),
-                                            max_y.map(|max_y| i32::max(max_y, metrics.maxy)).or(Some(metrics.maxy)),
-                                        ));
-                                    current_line.width += width;
-                                    current_line.spacing = i32::max(current_line.spacing, canvas.line_spacing()?);
-                                    current_line.height = u32::max(current_line.height, height);
-                                    current_line.segments.push(Segment {
-                                        text: text.chars()
-                                            .skip(start)
-                                            .take(end - start)
-                                            .collect(),
-                                        attributes: *attributes,
-                                        size: Dimen { width, height },
-                                        ascent: canvas.font_ascent()?,
-                                        min_y: min_y.unwrap_or(0),
-                                        max_y: max_y.unwrap_or(0),
-                                    });
-                                    lines.push(Line::default());
-                                    if end >= len - 1 {
-                                        break;
-                                    }
-                                    start = end;
-                                }
-                            } else {
-                                let current_line = lines.last_mut().unwrap();
-                                let (min_y, max_y) = text.chars()
-                                    .filter_map(|ch| canvas.glyph_metrics(ch).ok().and_then(|x| x))
-                                    .fold((None, None), |(min_y, max_y), metrics| (
-                                        min_y.map(|min_y| i32::min(min_y, metrics.miny)).or(Some(metrics.miny)),
-                                        max_y.map(|max_y| i32::max(max_y, metrics.maxy)).or(Some(metrics.maxy)),
-                                    ));
+                        .filter_map(|&index| choices.get(index))
+                        .map(|choice| Message::from(choice).message().plain())
+                        .collect();
 
-                                current_line.width += width;
-                                current_line.spacing = i32::max(current_line.spacing, canvas.line_spacing()?);
-                                current_line.height = u32::max(current_line.height, height);
-                                current_line.segments.push(Segment {
-                                    text,
-                                    attributes: *attributes,
-                                    size: Dimen { width, height },
-                                    ascent: canvas.font_ascent()?,
-                                    min_y: min_y.unwrap_or(0),
-                                    max_y: max_y.unwrap_or(0),
-                                });
-                            }
-                            if newline {
-                                lines.push(Line::default());
-                            }
-                            Ok(lines)
+                    // TODO: arr_width is a constant... so measure it once manually, not every frame
+                    let Dimen { width: arr_width, .. } = canvas.measure_text(String::from("=>"))?;
+
+                    let bounds = strings.iter()
+                        .try_fold(Dimen::default(), |size, string| -> game_engine::Result<Dimen> {
+                            let Dimen { width, .. } = canvas.measure_text(format!("{}", string))?;
+                            Ok(Dimen::new(
+                                u32::max(width, size.width),
+                                size.height + line_spacing as u32,
+                            ))
                         })?
+                        .extend(Dimen::new(32 + arr_width, 16));
+                    let options_box = Rect::from(
+                        Point::new(
+                            (size.width - bounds.width) as i32,
+                            (size.height - BOX_HEIGHT - bounds.height) as i32,
+                        ),
+                        bounds,
                     );
-                }
-                let lines = CALCULATED_LINES.as_ref().unwrap();
-                let mut y = (size.height - BOX_HEIGHT) as i32 + V_PADDING;
-                let mut printed = 0usize;
-                'done: for line in lines {
-                    let mut x = H_PADDING;
-                    for &Segment { ref text, attributes, size: Dimen { width, .. }, ascent, max_y, .. } in &line.segments {
-                        if text.is_empty() { break; }
-                        let to_print =
-                            if self.index.is_some() && printed + text.len() > self.index.unwrap() {
-                                text.chars().take(self.index.unwrap() - printed).collect()
-                            } else {
-                                text.to_owned()
-                            };
-                        printed += to_print.len();
-                        canvas.set_font(DEFAULT_FONT);
-                        canvas.set_color(Color::BLACK);
-                        if let Some(color) = attributes.color {
-                            canvas.set_color(color);
-                        }
-                        if let Some(font) = attributes.font {
-                            canvas.set_font(*font);
-                        }
-                        canvas.draw_text(Point::new(x, y + ascent - max_y), to_print)?;
-                        x += width as i32;
-                        if self.index.is_some() && printed == self.index.unwrap() {
-                            break 'done;
-                        }
+                    canvas.set_color(Color::WHITE);
+                    canvas.draw_rect_filled(options_box)?;
+                    let choice_boxes: Vec<_> = (0..strings.len())
+                        .map(|i| Rect::new(
+                            options_box.x,
+                            options_box.y + 8 + line_spacing * i as i32,
+                            bounds.width,
+                            line_spacing as u32,
+                        ))
+                        .collect();
+                    if let Some(selected) = choice_boxes.get(self.selection) {
+                        canvas.set_color(SELECTED_CHOICE_COLOR.into());
+                        canvas.draw_rect_filled(*selected)?;
                     }
-                    y += line.spacing;
-                }
-                if self.index.is_none() {
-                    if let Some(choices) = paragraph.choices() {
-                        canvas.set_font(DEFAULT_FONT);
-                        let line_spacing = canvas.line_spacing()?;
-                        let strings: Vec<_> = choices.iter().map(|choice| Message::from(choice).message().plain()).collect();
+                    self.layout.set_choices(choice_boxes);
 
-                        // TODO: arr_width is a constant... so measure it once manually, not every frame
-                        let Dimen { width: arr_width, .. } = canvas.measure_text(String::from("=>"))?;
-
-                        let bounds = strings.iter()
-                            .try_fold(Dimen::default(), |size, string| -> game_engine::Result<Dimen> {
-                                let Dimen { width, .. } = canvas.measure_text(format!("{}", string))?;
-                                Ok(Dimen::new(
-                                    u32::max(width, size.width),
-                                    size.height + line_spacing as u32,
-                                ))
-                            })?
-                            .extend(Dimen::new(32 + arr_width, 16));
-                        let options_box = Rect::from(
-                            Point::new(
-                                (size.width - bounds.width) as i32,
-                                (size.height - BOX_HEIGHT - bounds.height) as i32,
-                            ),
-                            bounds,
+                    // the time left shrinks from the right, along the bottom of the choices
+                    if let Some(timeout) = self.timeout {
+                        canvas.set_color(Color::from(TIMEOUT_COLOR));
+                        canvas.draw_rect_filled(Rect::new(
+                            options_box.x + 8,
+                            options_box.y + bounds.height as i32 - 8 + (8 - TIMEOUT_BAR_HEIGHT as i32) / 2,
+                            ((bounds.width - 16) as f32 * timeout).round() as u32,
+                            TIMEOUT_BAR_HEIGHT,
+                        ))?;
+                    }
+                    canvas.set_color(Color::BLACK);
+                    for (i, message) in strings.into_iter().enumerate() {
+                        let point = Point::new(
+                            options_box.x + 16 + arr_width as i32,
+                            options_box.y + 8 + line_spacing * i as i32,
                         );
-                        canvas.set_color(Color::WHITE);
-                        canvas.draw_rect_filled(options_box)?;
-                        canvas.set_color(Color::BLACK);
-                        for (i, message) in strings.into_iter().enumerate() {
+                        canvas.draw_text(point, message)?;
+                        if i == self.selection {
                             let point = Point::new(
-                                options_box.x + 16 + arr_width as i32,
+                                options_box.x + 8 as i32,
                                 options_box.y + 8 + line_spacing * i as i32,
                             );
-                            canvas.draw_text(point, message)?;
-                            if i == self.selection {
-                                let point = Point::new(
-                                    options_box.x + 8 as i32,
-                                    options_box.y + 8 + line_spacing * i as i32,
-                                );
-                                canvas.draw_text(point, String::from("=>"))?;
-                            }
+                            canvas.draw_text(point, String::from("=>"))?;
                         }
                     }
                 }
@@ -1,8 +1,14 @@
+mod backlog;
+mod controls;
 mod dialog;
 mod loading;
+mod speech_bubble;
 mod sprite;
 pub use self::{
+    backlog::BacklogDrawable,
+    controls::ControlsDrawable,
     dialog::DialogDrawable,
     loading::LoadingDrawable,
+    speech_bubble::SpeechBubbleDrawable,
     sprite::SpriteDrawable,
 };
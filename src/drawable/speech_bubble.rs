@@ -0,0 +1,150 @@
+use std::any::Any;
+use game_engine::prelude::*;
+
+use crate::model::pretty_string::PrettyString;
+use crate::text_layout::{self, TextLayoutCache};
+
+/// Draws the speech bubbles over the heads of whoever is saying them. The bubbles are drawn on the
+/// screen rather than in the world, so that they can be kept on the screen when the speaker is
+/// near the edge of it.
+#[derive(Default, Debug)]
+pub struct SpeechBubbleDrawable {
+    /// The position of each speaker, and what they are saying.
+    pub bubbles: Vec<(Point, PrettyString)>,
+    /// The point the camera is centered on.
+    pub focus: Point,
+    /// The bounds of the scene, which the camera is kept within.
+    pub bounds: Rect,
+    text_layouts: TextLayoutCache,
+}
+
+impl SpeechBubbleDrawable {
+    pub fn boxed() -> Box<dyn Drawable> {
+        Box::new(Self::default())
+    }
+
+    /// The part of the scene that is on the screen, which is found the same way the camera finds
+    /// it: centered on the focus, but kept within the bounds.
+    fn viewport(&self, size: Dimen) -> Rect {
+        fn keep_within(start: i32, min: i32, available: u32, length: u32) -> i32 {
+            i32::max(min, i32::min(start, min + available as i32 - length as i32))
+        }
+        Rect::new(
+            keep_within(self.focus.x - size.width as i32 / 2, self.bounds.x, self.bounds.width, size.width),
+            keep_within(self.focus.y - size.height as i32 / 2, self.bounds.y, self.bounds.height, size.height),
+            size.width,
+            size.height,
+        )
+    }
+}
+
+const MARGIN: i32 = 8;
+const H_PADDING: i32 = 8;
+const V_PADDING: i32 = 4;
+const MAX_WIDTH: u32 = 160;
+/// How far above the speaker's position the tail of the bubble points to.
+const HEAD_HEIGHT: i32 = 40;
+const TAIL_SIZE: i32 = 6;
+/// The space left between bubbles that had to be moved so as not to overlap.
+const GAP: i32 = 4;
+
+/// Keeps a span of the `length` within `min..max`, preferring the start if it does not fit.
+fn clamp(start: i32, length: i32, min: i32, max: i32) -> i32 {
+    i32::max(min, i32::min(start, max - length))
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.x < b.x + b.width as i32
+        && b.x < a.x + a.width as i32
+        && a.y < b.y + b.height as i32
+        && b.y < a.y + a.height as i32
+}
+
+/// Moves the bubble off of the bubbles that have already been placed, which happens when speakers
+/// stand close together or are kept on the screen at the same edge. It goes below the bubble it
+/// overlaps, or above it if there is no room below.
+fn make_room(mut bubble: Rect, placed: &[Rect], bottom: i32) -> Rect {
+    // each move clears one bubble, so this is enough moves to clear them all if they can be
+    for _ in 0..placed.len() {
+        let other = match placed.iter().find(|other| overlaps(bubble, **other)) {
+            Some(other) => other,
+            None => break,
+        };
+        let below = other.y + other.height as i32 + GAP;
+        bubble.y = if below + bubble.height as i32 <= bottom {
+            below
+        } else {
+            other.y - GAP - bubble.height as i32
+        };
+    }
+    bubble
+}
+
+impl Drawable for SpeechBubbleDrawable {
+    fn depth(&self) -> i32 {
+        ::std::i32::MAX - 3
+    }
+
+    fn render(&self, canvas: &mut dyn Canvas) -> game_engine::Result<()> {
+        if self.bubbles.is_empty() { return Ok(()); }
+
+        let size = canvas.size();
+        let viewport = self.viewport(size);
+        canvas.set_transform(Rect::from(Point::default(), size), Rect::from(Point::default(), size));
+
+        let mut placed = vec![];
+        for (anchor, text) in &self.bubbles {
+            let layout = self.text_layouts.layout(canvas, text, MAX_WIDTH)?;
+            let width = layout.width() as i32 + 2 * H_PADDING;
+            let height = layout.height() as i32 + 2 * V_PADDING;
+            let tip = Point::new(anchor.x - viewport.x, anchor.y - viewport.y - HEAD_HEIGHT);
+            let x = clamp(tip.x - width / 2, width, MARGIN, size.width as i32 - MARGIN);
+            let y = clamp(tip.y - TAIL_SIZE - height, height, MARGIN, size.height as i32 - MARGIN);
+            let bubble_box = make_room(Rect::new(x, y, width as u32, height as u32), &placed, size.height as i32 - MARGIN);
+            let moved = bubble_box.y != y;
+            let y = bubble_box.y;
+            placed.push(bubble_box);
+            canvas.set_color(Color::WHITE);
+            canvas.draw_rect_filled(bubble_box)?;
+            canvas.set_color(Color::BLACK);
+            canvas.draw_rect(bubble_box)?;
+
+            // the tail only points down at the speaker if the bubble could stay above them, and
+            // was not moved out of the way of another, whose bubble the tail would cross
+            let bottom = y + height;
+            if !moved && bottom + TAIL_SIZE <= tip.y + 1 {
+                let tail_x = clamp(tip.x, 1, x + TAIL_SIZE + 1, x + width - TAIL_SIZE - 1);
+                for row in 0..TAIL_SIZE {
+                    let half = TAIL_SIZE - row;
+                    canvas.set_color(Color::BLACK);
+                    canvas.draw_rect_filled(Rect::new(tail_x - half, bottom - 1 + row, 2 * half as u32 + 1, 1))?;
+                    if half > 1 {
+                        canvas.set_color(Color::WHITE);
+                        canvas.draw_rect_filled(Rect::new(tail_x - half + 1, bottom - 1 + row, 2 * half as u32 - 1, 1))?;
+                    }
+                }
+            }
+
+            for line in layout.lines() {
+                for run in layout.runs(line.glyphs.clone(), None) {
+                    canvas.set_font(text_layout::font(&run.attributes));
+                    let point = Point::new(x + H_PADDING + run.position.x, y + V_PADDING + run.position.y);
+                    if let Some(background) = run.attributes.background {
+                        canvas.set_color(Color::from(background));
+                        canvas.draw_rect_filled(Rect::new(point.x, point.y, run.width, run.height))?;
+                    }
+                    canvas.set_color(run.attributes.color.map(Color::from).unwrap_or(Color::BLACK));
+                    if run.attributes.is_underlined() {
+                        canvas.draw_rect_filled(Rect::new(point.x, point.y + canvas.font_ascent()? + 2, run.width, 1))?;
+                    }
+                    canvas.draw_text(point, run.text)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
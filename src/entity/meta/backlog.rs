@@ -0,0 +1,8 @@
+use game_engine::entity;
+use crate::drawable::BacklogDrawable;
+
+entity! {
+    pub Backlog {
+        BacklogDrawable::boxed(),
+    }
+}
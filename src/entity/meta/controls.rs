@@ -0,0 +1,8 @@
+use game_engine::entity;
+use crate::drawable::ControlsDrawable;
+
+entity! {
+    pub Controls {
+        ControlsDrawable::boxed(),
+    }
+}
@@ -1,9 +1,15 @@
 //! Meta entities, for things like the dialog system which needs to draw things but is really just
 //! a system and some resources
+mod backlog;
+mod controls;
 mod dialog;
 mod loading;
+mod speech_bubbles;
 
 pub use self::{
+    backlog::*,
+    controls::*,
     dialog::*,
     loading::*,
+    speech_bubbles::*,
 };
@@ -0,0 +1,8 @@
+use game_engine::entity;
+use crate::drawable::SpeechBubbleDrawable;
+
+entity! {
+    pub SpeechBubbles {
+        SpeechBubbleDrawable::boxed(),
+    }
+}
@@ -7,9 +7,10 @@ use crate::component::{
     velocity::Velocity,
     collision_box::CollisionBox,
     id::Id,
+    inventory::{Inventory, Wallet},
 };
 use crate::drawable::SpriteDrawable;
-use crate::sprite::{MALE_WALKCYCLE, MALE_PANTS};
+use crate::sprite::{MALE_WALKCYCLE, MALE_WALKCYCLE_ANIM, MALE_PANTS};
 
 entity! {
     pub Player(x: i32, y: i32) {
@@ -21,16 +22,18 @@ entity! {
         PreviousPosition::default(),
         Velocity::default(),
         CollisionBox::new(0, 0, 32, 32),
-        SpriteFrame::new(18),
+        Inventory::default(),
+        Wallet::default(),
+        SpriteFrame::new(MALE_WALKCYCLE_ANIM.walk_down.idle as i32),
         DrawDepth::new(0),
         SpriteDrawable::boxed(vec![&MALE_WALKCYCLE, &MALE_PANTS]),
         SpriteOrigin::new(16, 32),
         AnimationSpeed::new(0.5),
         WalkCycle::new([
-            (Direction::from_deg(270f64), 0..9),
-            (Direction::from_deg(190f64), 9..18),
-            (Direction::from_deg(90f64), 18..27),
-            (Direction::from_deg(0f64), 27..36),
+            (Direction::from_deg(270f64), MALE_WALKCYCLE_ANIM.walk_up),
+            (Direction::from_deg(190f64), MALE_WALKCYCLE_ANIM.walk_left),
+            (Direction::from_deg(90f64), MALE_WALKCYCLE_ANIM.walk_down),
+            (Direction::from_deg(0f64), MALE_WALKCYCLE_ANIM.walk_right),
         ].iter().cloned())
     }
 }
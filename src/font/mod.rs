@@ -2,3 +2,11 @@ use game_engine::prelude::*;
 
 include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
 
+
+/// The font of the family (the name of its TOML), in the style and size.
+pub fn find(family: &str, style: &str, size: u32) -> Option<&'static Font> {
+    FONTS
+        .iter()
+        .find(|(font_family, font_style, font_size, _)| *font_family == family && *font_style == style && *font_size == size)
+        .map(|(_, _, _, font)| *font)
+}
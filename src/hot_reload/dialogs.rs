@@ -0,0 +1,18 @@
+use std::{fs, path::Path};
+use inkgen::parse;
+
+/// Checks that an edited dialog still parses, so that mistakes are caught without waiting for a
+/// rebuild. The dialogs are compiled into the game, so that is as much as can be done at runtime.
+pub(super) fn check(path: &Path) {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: {}: {}", path.display(), error);
+            return;
+        }
+    };
+    match parse(source) {
+        Ok(..) => eprintln!("{} is valid, rebuild to see the changes", path.display()),
+        Err(error) => eprintln!("error: {}: is not valid ink: {:?}", path.display(), error),
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+use game_engine::prelude::*;
+
+#[allow(dead_code)]
+#[path = "../../build/diagnostic.rs"]
+mod diagnostic;
+#[allow(dead_code)]
+#[path = "../../build/schema.rs"]
+mod schema;
+#[allow(dead_code)]
+#[path = "../../build/collision.rs"]
+mod collision;
+
+mod dialogs;
+mod scene;
+mod sprites;
+mod tile_layers;
+mod watcher;
+
+pub use self::{
+    scene::{ReloadScene, spawn_objects},
+    sprites::HotSprites,
+    tile_layers::HotTileLayers,
+    watcher::AssetWatcher,
+};
+
+/// The directory that the asset sources are read from, which is the same one the build script
+/// used.
+fn resources_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+/// Reloads the assets while the game is running. The same TOML and TMX sources that the build
+/// script turns into code are polled for changes, and parsed using the build script's own schema.
+///
+/// Sprite frame tables and tile layers are swapped into the running game in place. When the map
+/// of the current scene is edited, the scene is entered again to spawn the edited objects, but the
+/// player is kept as they were, and the `State` is untouched.
+///
+/// The rest needs a rebuild to show up in the game:
+///
+/// *   Ink dialogs are compiled to Rust by inkgen, which has no way to run a story that it has
+///     only parsed, so an edited dialog is only parsed to report its errors right away.
+/// *   Images, tile sets, scenes and entities that did not exist when the game was built.
+pub fn register(game: Game<'a, 'b>) -> Game<'a, 'b> {
+    game.add_resource(AssetWatcher::new(resources_dir()))
+        .add_resource(HotSprites::default())
+        .add_resource(HotTileLayers::default())
+        .plugin(reload_assets)
+        .add_dispatcher(|builder|
+            builder
+                .with(ReloadScene::default(), "ReloadScene", &[])
+                .build()
+        )
+}
+
+fn reload_assets(world: &mut World) {
+    let changed = world.write_resource::<AssetWatcher>().poll();
+    for path in &changed {
+        eprintln!("Reloading {}", path.display());
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") if path.starts_with(resources_dir().join("sprite")) => sprites::reload(world, path),
+            Some("tmx") => world.write_resource::<HotTileLayers>().reload(path),
+            // tile sets and their images may be shared by any of the maps
+            Some("tsx") | Some("png") => world.write_resource::<HotTileLayers>().reload_all(),
+            Some("ink") => dialogs::check(path),
+            _ => eprintln!("{} cannot be reloaded, rebuild to see the changes", path.display()),
+        }
+    }
+    sprites::replace_stale(world);
+    tile_layers::apply(world);
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::HashSet,
+    sync::Mutex,
+};
+use game_engine::{system, prelude::*};
+use lazy_static::lazy_static;
+use crate::component::marker;
+use crate::entity::{door::Door, state_pickup::StatePickup};
+use crate::resource::state::{State, MainState};
+use crate::tile_grid::{scene_named, spawn_named};
+use super::{HotTileLayers, schema::TiledObject};
+
+/// Enters the current scene again when its map has been reloaded, so that the objects of the
+/// edited map are spawned in place of the old ones. The player is taken out of the scene while it
+/// is rebuilt, so they stay where they were, with everything they were carrying.
+#[derive(Default, Debug)]
+pub struct ReloadScene {
+    kept: Vec<Entity>,
+    entered: usize,
+}
+
+system! {
+    impl<'a> ReloadScene {
+        fn run(
+            &mut self,
+            entities: &Entities,
+            scene_manager: &mut SceneManager<'a>,
+            current_scene: &Resource<CurrentScene>,
+            player: &Component<marker::Player>,
+            scene_member: &mut Component<SceneMember>,
+            hot_tile_layers: &mut Resource<HotTileLayers>,
+        ) {
+            if !self.kept.is_empty() && hot_tile_layers.entered != self.entered {
+                for entity in self.kept.drain(..) {
+                    scene_member.insert(entity, SceneMember).unwrap();
+                }
+            }
+            if !hot_tile_layers.rebuild {
+                return;
+            }
+            hot_tile_layers.rebuild = false;
+            self.kept = (&*entities, &player).join().map(|(entity, _)| entity).collect();
+            for entity in &self.kept {
+                scene_member.remove(*entity);
+            }
+            self.entered = hot_tile_layers.entered;
+            scene_manager.change(current_scene.current());
+        }
+    }
+}
+
+/// An object of a reloaded map, resolved to what it will spawn.
+enum Object {
+    Door(&'static str, &'static dyn Scene, Rect, Point),
+    StatePickup(Rect, MainState),
+    Spawn(String, i32, i32),
+}
+
+/// Spawns the objects of the reloaded version of the map at `source`, in place of the ones that
+/// were generated from it, returning `false` if the map has not been reloaded. The walls are
+/// replaced along with the tile layers, so they are skipped here.
+pub fn spawn_objects(builder: &mut SceneBuilder<'a>, source: &str) -> bool {
+    let objects: Vec<_> = {
+        let hot_tile_layers = builder.get_resource::<HotTileLayers>();
+        let state = builder.get_resource::<State>();
+        let map = match hot_tile_layers.reloaded(source) {
+            Some(map) => map,
+            None => return false,
+        };
+        map.object_groups
+            .iter()
+            .flat_map(|group| &group.objects)
+            .filter(|object| match object.properties.get("when") {
+                Some(when) => when.parse().map(|when| state.is(when)).unwrap_or(false),
+                None => true,
+            })
+            .filter_map(|object| match resolve(object) {
+                Ok(object) => object,
+                Err(error) => {
+                    eprintln!("error: {}: object {}: {}", source, object.name, error);
+                    None
+                }
+            })
+            .collect()
+    };
+    for object in objects {
+        match object {
+            Object::Door(name, scene, rect, exit) => {
+                builder.add_entity(Door(name, scene, rect.x, rect.y, rect.width, rect.height, exit.x, exit.y));
+            }
+            Object::StatePickup(rect, state) => {
+                builder.add_entity(StatePickup(rect.x, rect.y, rect.width, rect.height, state));
+            }
+            Object::Spawn(name, x, y) => if !spawn_named(builder, &name, x, y) {
+                eprintln!("error: {}: there is no entity named {}, rebuild if it is new", source, name);
+            },
+        }
+    }
+    true
+}
+
+fn resolve(object: &TiledObject) -> Result<Option<Object>, String> {
+    let property = |name: &str| object.properties
+        .get(name)
+        .ok_or_else(|| format!("a {} must have the {} property", object.kind, name));
+    let int_property = |name: &str| match object.properties.get(name) {
+        Some(value) => value.parse::<i32>().map_err(|_| format!("{} is not a whole number", name)),
+        None => Ok(0),
+    };
+    let rect = Rect::new(object.x, object.y, object.width, object.height);
+    Ok(match object.kind.as_str() {
+        "door" => {
+            let scene = property("scene")?;
+            let scene = scene_named(scene).ok_or_else(|| format!("there is no scene named {}, rebuild if it is new", scene))?;
+            let exit = Point::new(int_property("exit_x")?, int_property("exit_y")?);
+            Some(Object::Door(intern(&object.name), scene, rect, exit))
+        }
+        "state_pickup" => {
+            let state = property("state")?;
+            let state = state.parse().map_err(|_| format!("there is no state named {}", state))?;
+            Some(Object::StatePickup(rect, state))
+        }
+        "spawn" => Some(Object::Spawn(object.name.clone(), object.x, object.y)),
+        "wall" => None,
+        kind => return Err(format!("{:?} is not one of door, wall, state_pickup or spawn", kind)),
+    })
+}
+
+lazy_static! {
+    static ref NAMES: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+}
+
+/// The doors are found by their name, which must live forever, so each name is leaked the first
+/// time that it is seen rather than every time the map is reloaded.
+fn intern(name: &str) -> &'static str {
+    let mut names = NAMES.lock().unwrap();
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(name);
+    name
+}
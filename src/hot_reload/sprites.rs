@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use game_engine::prelude::*;
+use crate::drawable::SpriteDrawable;
+use crate::image;
+use crate::sprite::SOURCES;
+use super::schema::SpriteSpec;
+
+/// The sprites that have been reloaded. The entities still refer to the generated sprites, so any
+/// outdated version of a sprite is replaced by the latest one whenever it is found in a
+/// `SpriteDrawable`.
+#[derive(Default, Debug)]
+pub struct HotSprites {
+    current: HashMap<PathBuf, &'static Sprite>,
+    stale: HashMap<usize, PathBuf>,
+}
+
+impl HotSprites {
+    fn replace(&mut self, source: &Path, sprite: &'static Sprite) {
+        let previous = self.current
+            .get(source)
+            .cloned()
+            .or_else(|| original(source));
+        if let Some(previous) = previous {
+            self.stale.insert(previous as *const Sprite as usize, source.to_owned());
+        }
+        self.current.insert(source.to_owned(), sprite);
+    }
+
+    fn latest(&self, sprite: &'static Sprite) -> Option<&'static Sprite> {
+        self.stale
+            .get(&(sprite as *const Sprite as usize))
+            .and_then(|source| self.current.get(source))
+            .cloned()
+    }
+}
+
+fn original(source: &Path) -> Option<&'static Sprite> {
+    SOURCES
+        .iter()
+        .find(|(path, _)| Path::new(path) == source)
+        .map(|(_, sprite)| *sprite)
+}
+
+pub(super) fn reload(world: &mut World, path: &Path) {
+    let sheet = match SpriteSpec::load(path) {
+        Ok(sheet) => sheet,
+        Err(diagnostic) => {
+            eprintln!("error: {}", diagnostic);
+            return;
+        }
+    };
+    // the images are loaded by the engine, so only the ones that were there when the game was
+    // built can be used
+    let image = match image::named(&sheet.image) {
+        Some(image) => image,
+        None => {
+            eprintln!("error: {}: `image`: there is no image named {:?}, rebuild if it is new", path.display(), sheet.image);
+            return;
+        }
+    };
+    // Sprites are expected to live forever, so the reloaded ones are leaked. There will only be as
+    // many of them as there are edits during one run of the game.
+    let frames: &'static [Rect] = Box::leak(
+        sheet.frames
+            .iter()
+            .map(|&[x, y, w, h]| Rect::new(x as i32, y as i32, w, h))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    );
+    let sprite = Box::leak(Box::new(Sprite::new(image, frames)));
+    world.write_resource::<HotSprites>().replace(path, sprite);
+}
+
+pub(super) fn replace_stale(world: &mut World) {
+    let hot_sprites = world.read_resource::<HotSprites>();
+    if hot_sprites.stale.is_empty() {
+        return;
+    }
+    let mut drawables = world.write_storage::<Box<dyn Drawable>>();
+    for drawable in (&mut drawables).join() {
+        if let Some(drawable) = drawable.as_any_mut().downcast_mut::<SpriteDrawable>() {
+            for sprite in &mut drawable.sprites {
+                if let Some(latest) = hot_sprites.latest(*sprite) {
+                    *sprite = latest;
+                }
+            }
+        }
+    }
+}
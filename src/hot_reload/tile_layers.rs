@@ -0,0 +1,253 @@
+use std::{
+    collections::{HashMap, BTreeMap},
+    path::{Path, PathBuf},
+};
+use game_engine::prelude::*;
+use crate::component::{marker, position::Position};
+use crate::entity::wall::Wall;
+use crate::model::{
+    tile_animation::{TileAnimation, TileFrame},
+    tile_properties::{TileProperties, TilePropertyLayer},
+};
+use crate::resource::{
+    tile_animations::TileAnimations,
+    tile_properties::TilePropertyLayers,
+    state::State,
+};
+use crate::tile_set::TILED;
+use super::collision::{merge_tiles, solid_tiles};
+use super::schema::{TiledTMXSpec, TiledSpec, TiledTileset};
+
+/// The map of the current scene, and which of its layers are at which depth, so that they can be
+/// replaced when the map is edited. Maps that have been reloaded are remembered, so that the
+/// edits are not lost when the scene is entered again.
+#[derive(Default)]
+pub struct HotTileLayers {
+    source: Option<PathBuf>,
+    layers: &'static [(i32, &'static str)],
+    reloaded: HashMap<PathBuf, TiledSpec>,
+    dirty: bool,
+    /// Set when the map of the current scene is reloaded, so that the scene is entered again to
+    /// spawn its edited objects.
+    pub(super) rebuild: bool,
+    /// How many times a scene has been entered, which tells when a rebuilt scene is ready.
+    pub(super) entered: usize,
+}
+
+impl HotTileLayers {
+    /// Called by a scene with the source of its map (the `SOURCE` of its generated module) and
+    /// the name of the layer shown at each depth.
+    pub fn watch(&mut self, source: &str, layers: &'static [(i32, &'static str)]) {
+        let source = PathBuf::from(source);
+        self.dirty = self.reloaded.contains_key(&source);
+        self.source = Some(source);
+        self.layers = layers;
+        self.entered += 1;
+    }
+
+    /// The reloaded version of a map, if it has been edited since the game started.
+    pub(super) fn reloaded(&self, source: &str) -> Option<&TiledSpec> {
+        self.reloaded.get(Path::new(source))
+    }
+
+    pub(super) fn reload(&mut self, path: &Path) {
+        match TiledTMXSpec::load(path) {
+            Ok(map) => {
+                self.reloaded.insert(path.to_owned(), map);
+                if self.source.as_ref().map(|source| source == path).unwrap_or(false) {
+                    self.dirty = true;
+                    self.rebuild = true;
+                }
+            }
+            Err(diagnostic) => eprintln!("error: {}", diagnostic),
+        }
+    }
+
+    pub(super) fn reload_all(&mut self) {
+        let sources: Vec<_> = self.reloaded
+            .keys()
+            .cloned()
+            .chain(self.source.clone())
+            .collect();
+        // a tile set does not change the objects, so there is no need to rebuild the scene
+        let rebuild = self.rebuild;
+        for source in sources {
+            self.reload(&source);
+        }
+        self.rebuild = rebuild;
+    }
+}
+
+pub(super) fn apply(world: &mut World) {
+    if let Some(walls) = apply_layers(world) {
+        replace_walls(world, walls);
+    }
+}
+
+/// Swaps the layers of the current scene for those of its reloaded map, and returns the walls of
+/// the map, if it has been reloaded.
+fn apply_layers(world: &World) -> Option<Vec<Rect>> {
+    let mut hot_tile_layers = world.write_resource::<HotTileLayers>();
+    if !hot_tile_layers.dirty {
+        return None;
+    }
+    hot_tile_layers.dirty = false;
+    let map = hot_tile_layers.source.as_ref().and_then(|source| hot_tile_layers.reloaded.get(source))?;
+    let mut tile_layers = world.write_resource::<TileLayers>();
+    let mut tile_animations = world.write_resource::<TileAnimations>();
+    let mut tile_property_layers = world.write_resource::<TilePropertyLayers>();
+    let mut tile_sets = HashMap::new();
+    for &(depth, name) in hot_tile_layers.layers {
+        let layer = match map.layers.iter().find(|layer| layer.name.to_uppercase() == name) {
+            Some(layer) => layer,
+            None => {
+                eprintln!("error: the map has no layer named {}, rebuild to remove it from the scene", name);
+                continue;
+            }
+        };
+        let mut tiles = vec![];
+        let mut animations = vec![];
+        let mut properties = vec![];
+        for (index, tile) in layer.tiles.iter().enumerate() {
+            if tile.gid == 0 {
+                tiles.push(None);
+                continue;
+            }
+            let set = match map.tilesets.iter().find(|set| set.contains(tile.gid)) {
+                Some(set) => set,
+                None => {
+                    eprintln!("error: layer {} has GID {}, which is not in any tile set", layer.name, tile.gid);
+                    tiles.push(None);
+                    continue;
+                }
+            };
+            let hot_set = tile_sets
+                .entry(set.const_name())
+                .or_insert_with(|| HotTileSet::new(set));
+            let tile_set = match hot_set.tile_set {
+                Some(tile_set) => tile_set,
+                None => {
+                    tiles.push(None);
+                    continue;
+                }
+            };
+            let id = tile.gid - set.firstgid;
+            tiles.push(Some(Tile { tile_set, index: id as usize }));
+            if let Some(animation) = hot_set.animations.get(&id) {
+                animations.push((index, *animation));
+            }
+            if let Some(tile_properties) = hot_set.properties.get(&id) {
+                properties.push((index, *tile_properties));
+            }
+        }
+        let size = Dimen::new(map.width, map.height);
+        let tiles: &'static [Option<Tile>] = Box::leak(tiles.into_boxed_slice());
+        tile_layers.set(depth, TileGrid::new(Point::new(0, 0), size, tiles.to_vec()));
+        tile_animations.set(depth, size, tiles, Box::leak(animations.into_boxed_slice()));
+        tile_property_layers.set(depth, Box::leak(Box::new(TilePropertyLayer::new(
+            map.width as usize,
+            map.tilewidth,
+            map.tileheight,
+            Box::leak(properties.into_boxed_slice()),
+        ))));
+    }
+    Some(walls(map, &world.read_resource::<State>()))
+}
+
+/// The walls of a map: its solid tiles, merged the same way that the build script merges them, and
+/// its `wall` objects.
+fn walls(map: &TiledSpec, state: &State) -> Vec<Rect> {
+    let tiles = merge_tiles(&solid_tiles(map), map.width)
+        .into_iter()
+        .map(|wall| Rect::new(
+            (wall.x * map.tilewidth) as i32,
+            (wall.y * map.tileheight) as i32,
+            wall.width * map.tilewidth,
+            wall.height * map.tileheight,
+        ));
+    let objects = map.object_groups
+        .iter()
+        .flat_map(|group| &group.objects)
+        .filter(|object| object.kind == "wall")
+        .filter(|object| match object.properties.get("when") {
+            Some(when) => when.parse().map(|when| state.is(when)).unwrap_or(false),
+            None => true,
+        })
+        .map(|object| Rect::new(object.x, object.y, object.width, object.height));
+    tiles.chain(objects).collect()
+}
+
+/// Replaces the walls of the scene. The walls are the only solid entities without a `Position`,
+/// because they never move.
+fn replace_walls(world: &mut World, walls: Vec<Rect>) {
+    {
+        let entities = world.entities();
+        let solid = world.read_storage::<marker::Solid>();
+        let position = world.read_storage::<Position>();
+        let mut delete = world.write_storage::<Delete>();
+        for (entity, _, _) in (&*entities, &solid, !&position).join() {
+            delete.insert(entity, Delete::default()).unwrap();
+        }
+    }
+    for wall in walls {
+        Wall(wall.x, wall.y, wall.width, wall.height)
+            .build(world.create_entity())
+            .with(SceneMember)
+            .build();
+    }
+}
+
+/// A tile set of a reloaded map, with its animations and properties rebuilt from the source. Like
+/// the reloaded sprites, these are leaked, because the tile layers expect them to live forever.
+struct HotTileSet {
+    tile_set: Option<&'static TileSet>,
+    animations: BTreeMap<u32, &'static TileAnimation>,
+    properties: BTreeMap<u32, &'static TileProperties>,
+}
+
+impl HotTileSet {
+    fn new(set: &TiledTileset) -> Self {
+        let const_name = set.const_name();
+        let tile_set = TILED
+            .iter()
+            .find(|(name, _)| *name == const_name)
+            .map(|(_, tile_set)| *tile_set);
+        if tile_set.is_none() {
+            eprintln!("error: tile set {} is new, rebuild to use it", set.name);
+        }
+        HotTileSet {
+            tile_set,
+            animations: set.animations
+                .iter()
+                .map(|(id, frames)| {
+                    let frames: &'static [TileFrame] = Box::leak(
+                        frames
+                            .iter()
+                            .map(|frame| TileFrame::new(frame.tileid as usize, frame.duration))
+                            .collect::<Vec<_>>()
+                            .into_boxed_slice()
+                    );
+                    let duration = frames.iter().map(|frame| frame.duration).sum();
+                    (*id, &*Box::leak(Box::new(TileAnimation::new(frames, duration))))
+                })
+                .collect(),
+            properties: set.properties
+                .iter()
+                .map(|(id, properties)| {
+                    let properties: &'static [(&'static str, &'static str)] = Box::leak(
+                        properties
+                            .iter()
+                            .map(|(name, value)| (leak(name), leak(value)))
+                            .collect::<Vec<_>>()
+                            .into_boxed_slice()
+                    );
+                    (*id, &*Box::leak(Box::new(TileProperties::new(properties))))
+                })
+                .collect(),
+        }
+    }
+}
+
+fn leak(string: &str) -> &'static str {
+    Box::leak(string.to_owned().into_boxed_str())
+}
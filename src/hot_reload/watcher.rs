@@ -0,0 +1,61 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the asset files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Keeps track of when each of the asset files was last modified, to find the ones that have
+/// changed.
+#[derive(Debug)]
+pub struct AssetWatcher {
+    root: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+    last_poll: Instant,
+}
+
+impl AssetWatcher {
+    pub fn new(root: PathBuf) -> Self {
+        let mut modified = HashMap::new();
+        scan(&root, &mut modified);
+        AssetWatcher { root, modified, last_poll: Instant::now() }
+    }
+
+    /// The files that have been modified (or created) since the last poll. Does nothing unless
+    /// the `POLL_INTERVAL` has passed since then.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return vec![];
+        }
+        self.last_poll = Instant::now();
+        let mut modified = HashMap::new();
+        scan(&self.root, &mut modified);
+        let changed = modified
+            .iter()
+            .filter(|(path, time)| self.modified.get(*path) != Some(time))
+            .map(|(path, _)| path.clone())
+            .collect();
+        self.modified = modified;
+        changed
+    }
+}
+
+fn scan(dir: &Path, modified: &mut HashMap<PathBuf, SystemTime>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(..) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            scan(&path, modified);
+        } else if path.extension().map(|extension| extension != "rs").unwrap_or(false) {
+            if let Ok(time) = entry.metadata().and_then(|metadata| metadata.modified()) {
+                modified.insert(path, time);
+            }
+        }
+    }
+}
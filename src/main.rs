@@ -1,6 +1,11 @@
 #![feature(range_contains, const_fn, generators, generator_trait, in_band_lifetimes)]
 #![deny(bare_trait_objects)]
 
+// the hot reloader shares the build script's schema, which expects the derive macros to be in scope
+#[cfg(feature = "hot-reload")]
+#[macro_use]
+extern crate serde_derive;
+
 pub mod component;
 pub mod constant;
 pub mod cutscene;
@@ -8,6 +13,8 @@ pub mod dialog;
 pub mod drawable;
 pub mod entity;
 pub mod font;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
 pub mod image;
 pub mod model;
 pub mod plugin;
@@ -15,6 +22,7 @@ pub mod resource;
 pub mod scene;
 pub mod sprite;
 pub mod system;
+pub mod text_layout;
 pub mod tile_grid;
 pub mod tile_set;
 
@@ -32,14 +40,18 @@ use crate::system::{
         apply_velocity::ApplyVelocity,
         camera_target::CameraTarget,
         loader::{HideLoader, ShowLoader},
+        speech_bubbles::ExpireSpeechBubbles,
         state_pickups::StatePickups,
     },
     drawable::{
         sprite::MaintainSpriteDrawable,
+        backlog::MaintainBacklogDrawable,
+        controls::MaintainControlsDrawable,
         dialog::MaintainDialogDrawable,
         loading::MaintainLoadingDrawable,
+        speech_bubble::MaintainSpeechBubbleDrawable,
     },
-    animations::AnimateWalkCycle,
+    animations::{AnimateWalkCycle, AnimateTiles},
 };
 
 fn main() -> game_engine::Result<()> {
@@ -59,10 +71,15 @@ fn main() -> game_engine::Result<()> {
                 .with(ApplyVelocity::default(), "ApplyVelocity", &["MoveByMovePath"])
                 .with(CameraTarget::default(), "CameraTarget", &["ApplyVelocity"])
                 .with(AnimateWalkCycle::default(), "AnimateWalkCycle", &["ApplyVelocity"])
+                .with(AnimateTiles::default(), "AnimateTiles", &[])
                 .with(EnterDoors::default(), "EnterDoors", &["ApplyVelocity"])
                 .with(StatePickups::default(), "StatePickups", &["ApplyVelocity"])
+                .with(ExpireSpeechBubbles::default(), "ExpireSpeechBubbles", &[])
                 .with(MaintainSpriteDrawable::default(), "MaintainSpriteDrawable", &["AnimateWalkCycle"])
                 .with(MaintainDialogDrawable::default(), "MaintainDialogDrawable", &[])
+                .with(MaintainBacklogDrawable::default(), "MaintainBacklogDrawable", &[])
+                .with(MaintainControlsDrawable::default(), "MaintainControlsDrawable", &[])
+                .with(MaintainSpeechBubbleDrawable::default(), "MaintainSpeechBubbleDrawable", &["CameraTarget", "ExpireSpeechBubbles"])
                 .build()
         )
 
@@ -0,0 +1,144 @@
+//! The commands that the tags of a dialog can give, to act on the game when their line is shown.
+//! A tag with a `:` in it is a command, like `#give:lockpick` or `#move:MysteryMan:19,11`, and any
+//! other tag is a dialog event for the cutscenes to wait for, like `#ComeOut`.
+//!
+//! This is shared with the build script, which uses it to check all the dialogs ahead of time, so
+//! it may only depend on `std`.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::condition::{parse_assignment, ConditionError, Expression};
+
+/// Every command, and how it is written.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("set", "set:name=value"),
+    ("give", "give:item or give:item:count"),
+    ("take", "take:item or take:item:count"),
+    ("money", "money:amount, which may be negative"),
+    ("state", "state:MainState"),
+    ("move", "move:Id:x,y, with as many x,y tile positions as there are points on the path"),
+    ("face", "face:Id:direction, where the direction is up, down, left or right"),
+    ("bark", "bark:Id:text, where the text may have markup"),
+    ("timeout", "timeout:frames or timeout:frames:choice, on a line with choices, where the choice is numbered from 1"),
+];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Facing {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Command {
+    /// Sets a dialog variable to the value of the expression.
+    Set(String, Expression),
+    /// Gives the player some of an item.
+    Give(String, u32),
+    /// Takes some of an item from the player.
+    Take(String, u32),
+    /// Gives the player money, or takes it if the amount is negative.
+    Money(i64),
+    /// Enters a `MainState`.
+    State(String),
+    /// Moves the entity with the `Id` along a path of tile positions.
+    Move(String, Vec<(i32, i32)>),
+    /// Turns the entity with the `Id` to face a direction.
+    Face(String, Facing),
+    /// Shows a speech bubble over the entity with the `Id`, which goes away on its own.
+    Bark(String, String),
+    /// Gives the choices of the line a number of frames to be picked in, after which the choice
+    /// with the number is picked, or the last one if there is none.
+    Timeout(u32, Option<usize>),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CommandError {
+    UnknownCommand { command: String },
+    InvalidArguments { command: String, usage: &'static str },
+    InvalidValue { error: ConditionError },
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand { command } => write!(f, "there is no command named {:?}", command),
+            CommandError::InvalidArguments { command, usage } => write!(f, "the {} command is written as {}", command, usage),
+            CommandError::InvalidValue { error } => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Parses a tag as a command, if it is one.
+pub fn parse(tag: &str) -> Option<Result<Command, CommandError>> {
+    let colon = tag.find(':')?;
+    Some(parse_command(tag[..colon].trim(), &tag[colon + 1..]))
+}
+
+fn parse_command(command: &str, arguments: &str) -> Result<Command, CommandError> {
+    let usage = match COMMANDS.iter().find(|(name, _)| *name == command) {
+        Some((_, usage)) => *usage,
+        None => return Err(CommandError::UnknownCommand { command: command.to_owned() }),
+    };
+    if command == "set" {
+        // the value may have a : in it, so it is not split up like the others
+        let (name, value) = parse_assignment(arguments).map_err(|error| CommandError::InvalidValue { error })?;
+        return Ok(Command::Set(name.to_owned(), value));
+    }
+    let invalid = || CommandError::InvalidArguments { command: command.to_owned(), usage };
+    if command == "bark" {
+        // the text may also have a : in it
+        let colon = arguments.find(':').ok_or_else(invalid)?;
+        let (target, text) = (arguments[..colon].trim(), arguments[colon + 1..].trim());
+        if target.is_empty() || text.is_empty() {
+            return Err(invalid());
+        }
+        return Ok(Command::Bark(target.to_owned(), text.to_owned()));
+    }
+    let arguments: Vec<&str> = arguments.split(':').map(str::trim).collect();
+    if arguments.iter().any(|argument| argument.is_empty()) {
+        return Err(invalid());
+    }
+    match (command, arguments.as_slice()) {
+        ("give", [item]) => Ok(Command::Give(item.to_string(), 1)),
+        ("give", [item, count]) => Ok(Command::Give(item.to_string(), count.parse().map_err(|_| invalid())?)),
+        ("take", [item]) => Ok(Command::Take(item.to_string(), 1)),
+        ("take", [item, count]) => Ok(Command::Take(item.to_string(), count.parse().map_err(|_| invalid())?)),
+        ("money", [amount]) => Ok(Command::Money(amount.parse().map_err(|_| invalid())?)),
+        ("state", [state]) => Ok(Command::State(state.to_string())),
+        ("move", _) if arguments.len() > 1 => {
+            let path = arguments[1..]
+                .iter()
+                .map(|point| {
+                    let comma = point.find(',').ok_or_else(invalid)?;
+                    let x = point[..comma].trim().parse().map_err(|_| invalid())?;
+                    let y = point[comma + 1..].trim().parse().map_err(|_| invalid())?;
+                    Ok((x, y))
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Command::Move(arguments[0].to_owned(), path))
+        }
+        ("face", [target, direction]) => {
+            let facing = match *direction {
+                "up" => Facing::Up,
+                "down" => Facing::Down,
+                "left" => Facing::Left,
+                "right" => Facing::Right,
+                _ => return Err(invalid()),
+            };
+            Ok(Command::Face(target.to_string(), facing))
+        }
+        ("timeout", [frames]) => Ok(Command::Timeout(parse_positive(frames).ok_or_else(invalid)?, None)),
+        ("timeout", [frames, choice]) => Ok(Command::Timeout(
+            parse_positive(frames).ok_or_else(invalid)?,
+            Some(parse_positive(choice).ok_or_else(invalid)? as usize),
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a number that is more than 0.
+fn parse_positive(string: &str) -> Option<u32> {
+    string.parse().ok().filter(|&number| number > 0)
+}
@@ -0,0 +1,492 @@
+//! The conditions that decide whether a line or choice of a dialog is shown, and the expressions
+//! that the dialogs set variables to. They are made of numbers, `"text"`, `true` and `false`, the
+//! dialog variables, calls to the functions that the game provides (`has_item("key")`, `money()`
+//! and `state_is("HideSomewhere")`), `!`, `&&`, `||`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `+`, `-`
+//! and parentheses. Within text, `\"` is a `"` and `\\` is a `\`.
+//!
+//! This is shared with the build script, which uses it to check all the dialogs ahead of time, so
+//! it may only depend on `std`.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+
+/// The functions that the game provides to the dialogs, and how many arguments each one takes.
+pub const FUNCTIONS: &[(&str, usize)] = &[
+    ("has_item", 1),
+    ("money", 0),
+    ("state_is", 1),
+];
+
+/// Symbols are matched in order, so the longer ones must come first.
+const SYMBOLS: &[&str] = &["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "(", ")", ","];
+
+/// The binary operators, from the loosest to the tightest binding.
+const PRECEDENCE: &[&[(&str, Operator)]] = &[
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ],
+    &[("+", Operator::Add), ("-", Operator::Subtract)],
+];
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Value {
+    Bool(bool),
+    Number(i64),
+    Text(String),
+}
+
+/// A variable that has never been set is `false`.
+impl Default for Value {
+    fn default() -> Self {
+        Value::Bool(false)
+    }
+}
+
+impl Value {
+    /// Whether the value lets a line be shown: `true`, any number but 0, or any text but "".
+    pub fn is_true(&self) -> bool {
+        match self {
+            Value::Bool(value) => *value,
+            Value::Number(value) => *value != 0,
+            Value::Text(value) => !value.is_empty(),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", value),
+            Value::Text(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Operator {
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+}
+
+impl Operator {
+    /// Applies the operator to two values. Values of different types are never less or greater
+    /// than each other, and only numbers and text can be added.
+    fn apply(self, left: Value, right: Value) -> Value {
+        let ordering = match (&left, &right) {
+            (Value::Number(left), Value::Number(right)) => Some(left.cmp(right)),
+            (Value::Text(left), Value::Text(right)) => Some(left.cmp(right)),
+            _ => None,
+        };
+        match self {
+            Operator::And => Value::Bool(left.is_true() && right.is_true()),
+            Operator::Or => Value::Bool(left.is_true() || right.is_true()),
+            Operator::Equal => Value::Bool(left == right),
+            Operator::NotEqual => Value::Bool(left != right),
+            Operator::Less => Value::Bool(ordering == Some(Ordering::Less)),
+            Operator::LessEqual => Value::Bool(ordering.map(|ordering| ordering != Ordering::Greater).unwrap_or(false)),
+            Operator::Greater => Value::Bool(ordering == Some(Ordering::Greater)),
+            Operator::GreaterEqual => Value::Bool(ordering.map(|ordering| ordering != Ordering::Less).unwrap_or(false)),
+            Operator::Add => match (left, right) {
+                (Value::Number(left), Value::Number(right)) => Value::Number(left.saturating_add(right)),
+                (Value::Text(left), Value::Text(right)) => Value::Text(left + &right),
+                _ => Value::default(),
+            },
+            Operator::Subtract => match (left, right) {
+                (Value::Number(left), Value::Number(right)) => Value::Number(left.saturating_sub(right)),
+                _ => Value::default(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Expression {
+    Value(Value),
+    Variable(String),
+    Call(String, Vec<Expression>),
+    Not(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+/// What an expression can see of the game.
+pub trait Context {
+    /// The value of a dialog variable.
+    fn variable(&self, name: &str) -> Value;
+    /// Calls one of the `FUNCTIONS`.
+    fn call(&self, function: &str, arguments: &[Value]) -> Value;
+}
+
+impl Expression {
+    pub fn evaluate(&self, context: &dyn Context) -> Value {
+        match self {
+            Expression::Value(value) => value.clone(),
+            Expression::Variable(name) => context.variable(name),
+            Expression::Call(function, arguments) => {
+                let arguments: Vec<_> = arguments.iter().map(|argument| argument.evaluate(context)).collect();
+                context.call(function, &arguments)
+            }
+            Expression::Not(expression) => Value::Bool(!expression.evaluate(context).is_true()),
+            // the right side of && and || is only worked out if it is needed
+            Expression::Binary(left, Operator::And, right) => Value::Bool(left.evaluate(context).is_true() && right.evaluate(context).is_true()),
+            Expression::Binary(left, Operator::Or, right) => Value::Bool(left.evaluate(context).is_true() || right.evaluate(context).is_true()),
+            Expression::Binary(left, operator, right) => operator.apply(left.evaluate(context), right.evaluate(context)),
+        }
+    }
+
+    /// Every function that the expression calls, along with how many arguments it is called with.
+    pub fn calls(&self) -> Vec<(&str, usize)> {
+        match self {
+            Expression::Value(..) | Expression::Variable(..) => vec![],
+            Expression::Call(function, arguments) => std::iter::once((function.as_str(), arguments.len()))
+                .chain(arguments.iter().flat_map(Expression::calls))
+                .collect(),
+            Expression::Not(expression) => expression.calls(),
+            Expression::Binary(left, _, right) => left.calls().into_iter().chain(right.calls()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ConditionError {
+    UnexpectedEnd,
+    Unexpected { found: String, column: usize },
+    UnterminatedText { column: usize },
+    InvalidNumber { column: usize },
+    MissingValue,
+    InvalidName { name: String },
+}
+
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ConditionError::UnexpectedEnd => write!(f, "the condition ends too soon"),
+            ConditionError::Unexpected { found, column } => write!(f, "{} was not expected at character {} of the condition", found, column),
+            ConditionError::UnterminatedText { column } => write!(f, "the text at character {} of the condition is never closed with a \"", column),
+            ConditionError::InvalidNumber { column } => write!(f, "the number at character {} of the condition is too big", column),
+            ConditionError::MissingValue => write!(f, "a variable must be set to a value, as in name=value"),
+            ConditionError::InvalidName { name } => write!(f, "{:?} is not a valid variable name", name),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Lexeme {
+    Number(i64),
+    Text(String),
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl Display for Lexeme {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Lexeme::Number(number) => write!(f, "{}", number),
+            Lexeme::Text(text) => write!(f, "{:?}", text),
+            Lexeme::Name(name) => write!(f, "{}", name),
+            Lexeme::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+/// Splits a condition into its numbers, text, names and symbols, along with the column that each
+/// starts at. Columns are counted in characters, starting at 1.
+fn lex(source: &str) -> Result<Vec<(Lexeme, usize)>, ConditionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut lexemes = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+        } else if ch.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let number = digits.parse().map_err(|_| ConditionError::InvalidNumber { column })?;
+            lexemes.push((Lexeme::Number(number), column));
+        } else if ch == '_' || ch.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i] == '_' || chars[i].is_alphanumeric()) {
+                i += 1;
+            }
+            lexemes.push((Lexeme::Name(chars[start..i].iter().collect()), column));
+        } else if ch == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(ConditionError::UnterminatedText { column }),
+                    Some(&'"') => break,
+                    Some(&'\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some(&ch) => text.push(ch),
+                            None => return Err(ConditionError::UnterminatedText { column }),
+                        }
+                    }
+                    Some(&ch) => text.push(ch),
+                }
+                i += 1;
+            }
+            i += 1;
+            lexemes.push((Lexeme::Text(text), column));
+        } else {
+            let rest: String = chars[i..usize::min(i + 2, chars.len())].iter().collect();
+            match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+                Some(symbol) => {
+                    lexemes.push((Lexeme::Symbol(*symbol), column));
+                    i += symbol.len();
+                }
+                None => return Err(ConditionError::Unexpected { found: ch.to_string(), column }),
+            }
+        }
+    }
+    Ok(lexemes)
+}
+
+struct Parser {
+    lexemes: Vec<(Lexeme, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self) -> ConditionError {
+        match self.lexemes.get(self.position) {
+            Some((lexeme, column)) => ConditionError::Unexpected { found: lexeme.to_string(), column: *column },
+            None => ConditionError::UnexpectedEnd,
+        }
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        match self.lexemes.get(self.position) {
+            Some((Lexeme::Symbol(found), _)) if *found == symbol => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ConditionError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Parses the operators of one level of precedence, and everything that binds more tightly.
+    fn binary(&mut self, level: usize) -> Result<Expression, ConditionError> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        'outer: loop {
+            for &(symbol, operator) in PRECEDENCE[level] {
+                if self.eat(symbol) {
+                    let right = self.binary(level + 1)?;
+                    left = Expression::Binary(Box::new(left), operator, Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, ConditionError> {
+        if self.eat("!") {
+            Ok(Expression::Not(Box::new(self.unary()?)))
+        } else if self.eat("-") {
+            let expression = self.unary()?;
+            Ok(Expression::Binary(Box::new(Expression::Value(Value::Number(0))), Operator::Subtract, Box::new(expression)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, ConditionError> {
+        if self.eat("(") {
+            let expression = self.binary(0)?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+        let lexeme = match self.lexemes.get(self.position) {
+            Some((lexeme, _)) => lexeme.clone(),
+            None => return Err(ConditionError::UnexpectedEnd),
+        };
+        match lexeme {
+            Lexeme::Number(number) => {
+                self.position += 1;
+                Ok(Expression::Value(Value::Number(number)))
+            }
+            Lexeme::Text(text) => {
+                self.position += 1;
+                Ok(Expression::Value(Value::Text(text)))
+            }
+            Lexeme::Name(ref name) if name == "true" || name == "false" => {
+                self.position += 1;
+                Ok(Expression::Value(Value::Bool(name == "true")))
+            }
+            Lexeme::Name(name) => {
+                self.position += 1;
+                if !self.eat("(") {
+                    return Ok(Expression::Variable(name));
+                }
+                let mut arguments = vec![];
+                if !self.eat(")") {
+                    loop {
+                        arguments.push(self.binary(0)?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expression::Call(name, arguments))
+            }
+            Lexeme::Symbol(..) => Err(self.error()),
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Expression, ConditionError> {
+    let mut parser = Parser { lexemes: lex(source)?, position: 0 };
+    let expression = parser.binary(0)?;
+    if parser.position != parser.lexemes.len() {
+        return Err(parser.error());
+    }
+    Ok(expression)
+}
+
+/// Parses the setting of a dialog variable, like `met_him = true`, into the name of the variable
+/// and the value to set it to.
+pub fn parse_assignment(source: &str) -> Result<(&str, Expression), ConditionError> {
+    let equals = source.find('=').ok_or(ConditionError::MissingValue)?;
+    let name = source[..equals].trim();
+    if !is_variable_name(name) {
+        return Err(ConditionError::InvalidName { name: name.to_owned() });
+    }
+    Ok((name, parse(&source[equals + 1..])?))
+}
+
+/// Whether a name can be used for a dialog variable.
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|ch: char| ch.is_ascii_digit())
+        && name.chars().all(|ch| ch == '_' || ch.is_alphanumeric())
+        && name != "true"
+        && name != "false"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestContext;
+
+    impl Context for TestContext {
+        fn variable(&self, name: &str) -> Value {
+            match name {
+                "visits" => Value::Number(2),
+                _ => Value::default(),
+            }
+        }
+
+        fn call(&self, function: &str, arguments: &[Value]) -> Value {
+            match (function, arguments) {
+                ("has_item", [Value::Text(item)]) => Value::Bool(item == "key"),
+                ("money", []) => Value::Number(25),
+                _ => Value::default(),
+            }
+        }
+    }
+
+    fn evaluate(source: &str) -> Value {
+        parse(source).unwrap().evaluate(&TestContext)
+    }
+
+    fn variable(name: &str) -> Box<Expression> {
+        Box::new(Expression::Variable(name.to_owned()))
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            parse("a || b && c"),
+            Ok(Expression::Binary(
+                variable("a"),
+                Operator::Or,
+                Box::new(Expression::Binary(variable("b"), Operator::And, variable("c"))),
+            )),
+        );
+        assert_eq!(evaluate("1 + 2 == 3 && false || true"), Value::Bool(true));
+        assert_eq!(evaluate("visits + 1 == 3"), Value::Bool(true));
+        assert_eq!(evaluate("5 - 2 - 1"), Value::Number(2));
+        assert_eq!(evaluate("!false && false"), Value::Bool(false));
+        assert_eq!(evaluate("-visits + 3"), Value::Number(1));
+    }
+
+    #[test]
+    fn parenthesised_comparisons() {
+        assert_eq!(evaluate("(money() >= 20) && has_item(\"key\")"), Value::Bool(true));
+        assert_eq!(evaluate("(money() < 20) || !has_item(\"key\")"), Value::Bool(false));
+        assert_eq!(evaluate("(1 < 2) == (3 > 4)"), Value::Bool(false));
+        assert_eq!(evaluate("!(visits == 2)"), Value::Bool(false));
+        assert_eq!(evaluate("((visits))"), Value::Number(2));
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(evaluate(r#""say \"hi\"""#), Value::Text("say \"hi\"".to_owned()));
+        assert_eq!(evaluate(r#""back\\slash""#), Value::Text("back\\slash".to_owned()));
+        assert_eq!(evaluate(r#""a\\" == "a\\""#), Value::Bool(true));
+        assert_eq!(evaluate(r#"has_item("\k\e\y")"#), Value::Bool(true));
+        assert_eq!(parse(r#""never closed\""#), Err(ConditionError::UnterminatedText { column: 1 }));
+    }
+
+    #[test]
+    fn error_columns() {
+        assert_eq!(parse("1 +"), Err(ConditionError::UnexpectedEnd));
+        assert_eq!(parse("1 2"), Err(ConditionError::Unexpected { found: "2".to_owned(), column: 3 }));
+        assert_eq!(parse("a && #"), Err(ConditionError::Unexpected { found: "#".to_owned(), column: 6 }));
+        assert_eq!(parse("has_item(1,)"), Err(ConditionError::Unexpected { found: ")".to_owned(), column: 12 }));
+        assert_eq!(parse("(a"), Err(ConditionError::UnexpectedEnd));
+        assert_eq!(parse("a == \"abc"), Err(ConditionError::UnterminatedText { column: 6 }));
+        assert_eq!(parse("99999999999999999999"), Err(ConditionError::InvalidNumber { column: 1 }));
+        // columns count characters rather than bytes
+        assert_eq!(parse("\"é\" #"), Err(ConditionError::Unexpected { found: "#".to_owned(), column: 5 }));
+    }
+
+    #[test]
+    fn assignments() {
+        assert_eq!(parse_assignment("met_him = true"), Ok(("met_him", Expression::Value(Value::Bool(true)))));
+        assert_eq!(
+            parse_assignment("visits=visits + 1"),
+            Ok(("visits", Expression::Binary(variable("visits"), Operator::Add, Box::new(Expression::Value(Value::Number(1)))))),
+        );
+        assert_eq!(parse_assignment("met_him"), Err(ConditionError::MissingValue));
+        assert_eq!(parse_assignment("1st = 2"), Err(ConditionError::InvalidName { name: "1st".to_owned() }));
+        assert_eq!(parse_assignment("true = 1"), Err(ConditionError::InvalidName { name: "true".to_owned() }));
+        assert_eq!(parse_assignment(" = 1"), Err(ConditionError::InvalidName { name: "".to_owned() }));
+        assert_eq!(parse_assignment("met_him = "), Err(ConditionError::UnexpectedEnd));
+    }
+}
@@ -1,11 +1,14 @@
 use game_engine::prelude::*;
-use serde::Deserialize;
-use ron::de::from_str;
 use inkgen::runtime::Story;
 
 use crate::component::{
     id::Id,
     behavior::MovePath,
+    speech_bubble::SpeechBubble,
+};
+use crate::model::{
+    dialog_event::DialogEvent,
+    pretty_string::PrettyString,
 };
 use crate::resource::{
     dialog::{DialogEvents, DialogMessages},
@@ -13,13 +16,15 @@ use crate::resource::{
 };
 
 #[derive(Clone, Debug)]
-pub enum Step<E>
-where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
+pub enum Step {
     Move(Id, &'static [Point<f32>]),
     AwaitMoveEnd(Id),
-    AwaitDialogEvent(E),
+    /// Waits for the dialog to emit the event, which may already have happened.
+    AwaitDialogEvent(DialogEvent),
     AwaitDialogEnd,
     StartDialog(fn() -> Story),
+    /// Shows a speech bubble over the entity, without waiting for it to go away.
+    Bark(Id, &'static str),
     Delay(u32),
     Break,
     StateChange(MainState),
@@ -30,28 +35,25 @@ pub trait Cutscene: Sync + Send {
     fn run(&mut self, world: &mut World);
 }
 
-pub struct StandardCutscene<E>
-where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
-    steps: &'static [Step<E>],
+pub struct StandardCutscene {
+    steps: &'static [Step],
     delay: u32,
 }
 
-impl<E> StandardCutscene<E>
-where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
-    pub const fn new(steps: &'static [Step<E>]) -> Self {
+impl StandardCutscene {
+    pub const fn new(steps: &'static [Step]) -> Self {
         Self {
             steps,
             delay: 0,
         }
     }
 
-    pub fn boxed(steps: &'static [Step<E>]) -> Box<Self> {
+    pub fn boxed(steps: &'static [Step]) -> Box<Self> {
         Box::new(Self::new(steps))
     }
 }
 
-impl<E> Cutscene for StandardCutscene<E>
-where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
+impl Cutscene for StandardCutscene {
     fn is_over(&self) -> bool {
         self.steps.is_empty() && self.delay == 0
     }
@@ -103,12 +105,8 @@ where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
                     self.steps = &self.steps[1..];
                 }
                 Some(Step::AwaitDialogEvent(target)) => {
-                    let emitted = world.read_resource::<DialogEvents>()
-                        .iter()
-                        .map(|string| from_str(string).unwrap())
-                        .collect::<Vec<_>>()
-                        .contains(target);
-                    if emitted {
+                    let story = world.read_resource::<DialogMessages>().story();
+                    if world.write_resource::<DialogEvents>().take(story, target) {
                         self.steps = &self.steps[1..];
                     } else {
                         break
@@ -121,6 +119,20 @@ where for<'a> E: Deserialize<'a> + PartialEq + Sync + Send + 'static {
                         break
                     }
                 }
+                Some(Step::Bark(target, text)) => {
+                    self.steps = &self.steps[1..];
+                    let text = PrettyString::parse(text).unwrap_or_else(|error| {
+                        eprintln!("Could not parse bark {:?}: {}", text, error);
+                        PrettyString::plain_text(text)
+                    });
+                    let entities = world.entities();
+                    let id = world.read_storage::<Id>();
+                    for (entity, id) in (&*entities, &id).join() {
+                        if id == target {
+                            world.write_storage::<SpeechBubble>().insert(entity, SpeechBubble::new(text.clone())).unwrap();
+                        }
+                    }
+                }
                 Some(Step::StartDialog(story)) => {
                     self.steps = &self.steps[1..];
                     world.write_resource::<DialogMessages>().start(story())
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+/// The events that the dialogs emit with their tags, as in `#ComeOut`, for the cutscenes to wait
+/// for. A tag is parsed as RON, so an event can carry data too, as in `#Arrive("alley")`.
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug)]
+pub enum DialogEvent {
+    ComeOut,
+}
@@ -1,2 +1,27 @@
+//! The items that can be carried in an `Inventory`.
+//!
+//! This is shared with the build script, which checks that the dialogs only give and take items
+//! that exist, so it may only depend on `std`.
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Item(&'static str);
+
+pub const LOCKPICK: Item = Item("lockpick");
+pub const CALLING_CARD: Item = Item("calling_card");
+
+/// Every item, so that they can be found by their names.
+pub const ITEMS: &[Item] = &[LOCKPICK, CALLING_CARD];
+
+impl Item {
+    pub const fn new(name: &'static str) -> Self {
+        Item(name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn find(name: &str) -> Option<Item> {
+        ITEMS.iter().find(|item| item.0 == name).cloned()
+    }
+}
@@ -0,0 +1,219 @@
+//! The markup used in the text of the dialogs: an optional `Speaker:` or `Speaker[expression]:`
+//! prefix, then text in which `<rule:...>` applies a text style rule to everything up to the
+//! matching `>`. A literal `<` or `>` is written as `<<` or `<>`. `<pause:N>` is not a rule, but
+//! holds the typewriter for N frames once the text before it has been shown. `<if:condition>` at
+//! the very start of the text only shows the line or choice when the condition is true. Its
+//! condition ends at the first `>` outside of parentheses and quotes, so a comparison has to be
+//! put in parentheses, as in `<if:(money() >= 20)>`.
+//!
+//! This is shared with the build script, which uses it to check all the dialogs ahead of time, so
+//! it may only depend on `std`.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::condition::{parse, Expression, ConditionError};
+
+/// The name of the pause tag, which may not be used as the name of a rule.
+pub const PAUSE: &str = "pause";
+
+/// The name of the condition tag, which may not be used as the name of a rule either.
+pub const CONDITION: &str = "if";
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Token {
+    /// The start of a rule, and the column that it starts at.
+    Open(String, usize),
+    Close,
+    Text(String),
+    /// A pause, in frames.
+    Pause(u32),
+    /// The condition for the text to be shown, which is always the first token.
+    Condition(Expression),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum MarkupError {
+    EmptyRuleName { column: usize },
+    UnterminatedRuleName { column: usize },
+    UnclosedRule { rule: String, column: usize },
+    UnexpectedClose { column: usize },
+    UnknownRule { rule: String, column: usize },
+    InvalidPause { column: usize },
+    InvalidCondition { column: usize, error: ConditionError },
+    MisplacedCondition { column: usize },
+}
+
+impl Display for MarkupError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MarkupError::EmptyRuleName { column } => write!(f, "column {}: a rule must have a name, as in <rule:...>", column),
+            MarkupError::UnterminatedRuleName { column } => write!(f, "column {}: the rule name is not followed by a :", column),
+            MarkupError::UnclosedRule { rule, column } => write!(f, "column {}: <{}: is never closed with a >", column, rule),
+            MarkupError::UnexpectedClose { column } => write!(f, "column {}: there is no rule for this > to close", column),
+            MarkupError::UnknownRule { rule, column } => write!(f, "column {}: there is no rule named {:?}", column, rule),
+            MarkupError::InvalidPause { column } => write!(f, "column {}: a pause must be a number of frames, as in <pause:30>", column),
+            MarkupError::InvalidCondition { column, error } => write!(f, "column {}: {}", column, error),
+            MarkupError::MisplacedCondition { column } => write!(f, "column {}: <if:...> must come before all of the text", column),
+        }
+    }
+}
+
+/// Splits the speaker from the rest of a line, if it has one. A `:` only marks the speaker if it
+/// comes before any rule, as every rule contains one too.
+pub fn split_speaker(line: &str) -> (Option<&str>, &str) {
+    match (line.find(':'), line.find('<')) {
+        (Some(colon), Some(rule)) if rule < colon => (None, line),
+        (Some(colon), _) => (Some(line[..colon].trim()), line[colon + 1..].trim()),
+        (None, _) => (None, line),
+    }
+}
+
+/// Splits the expression from a speaker, if they have one, as in `You[worried]`.
+pub fn split_expression(speaker: &str) -> (&str, Option<&str>) {
+    match speaker.find('[') {
+        Some(open) if speaker.ends_with(']') => (
+            speaker[..open].trim(),
+            Some(speaker[open + 1..speaker.len() - 1].trim()),
+        ),
+        _ => (speaker, None),
+    }
+}
+
+/// Splits text into the rules and the text that they apply to. Columns are counted in characters,
+/// starting at 1.
+pub fn tokenize(text: &str) -> Result<Vec<Token>, MarkupError> {
+    let mut tokens = vec![];
+    let mut open = vec![];
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some((i, ch)) = chars.next() {
+        let column = i + 1;
+        match ch {
+            '<' => match chars.peek() {
+                Some(&(_, '<')) => { chars.next(); push_text(&mut tokens, '<'); }
+                Some(&(_, '>')) => { chars.next(); push_text(&mut tokens, '>'); }
+                _ => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ':')) => break,
+                            Some((_, '<')) | Some((_, '>')) | None => return Err(MarkupError::UnterminatedRuleName { column }),
+                            Some((_, ch)) => name.push(ch),
+                        }
+                    }
+                    if name.trim().is_empty() {
+                        return Err(MarkupError::EmptyRuleName { column });
+                    }
+                    if name.trim() == PAUSE {
+                        let mut frames = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, '>')) => break,
+                                Some((_, ch)) => frames.push(ch),
+                                None => return Err(MarkupError::InvalidPause { column }),
+                            }
+                        }
+                        let frames = frames.trim().parse().map_err(|_| MarkupError::InvalidPause { column })?;
+                        tokens.push(Token::Pause(frames));
+                        continue;
+                    }
+                    if name.trim() == CONDITION {
+                        if !tokens.is_empty() {
+                            return Err(MarkupError::MisplacedCondition { column });
+                        }
+                        let mut source = String::new();
+                        let mut depth = 0;
+                        let mut quoted = false;
+                        let mut escaped = false;
+                        loop {
+                            match chars.next() {
+                                Some((_, '>')) if depth == 0 && !quoted => break,
+                                Some((_, ch)) => {
+                                    match ch {
+                                        _ if escaped => escaped = false,
+                                        '\\' if quoted => escaped = true,
+                                        '"' => quoted = !quoted,
+                                        '(' if !quoted => depth += 1,
+                                        ')' if !quoted && depth > 0 => depth -= 1,
+                                        _ => {}
+                                    }
+                                    source.push(ch);
+                                }
+                                None => return Err(MarkupError::UnclosedRule { rule: name, column }),
+                            }
+                        }
+                        let condition = parse(&source).map_err(|error| MarkupError::InvalidCondition { column, error })?;
+                        tokens.push(Token::Condition(condition));
+                        continue;
+                    }
+                    open.push((name.clone(), column));
+                    tokens.push(Token::Open(name, column));
+                }
+            },
+            '>' => {
+                if open.pop().is_none() {
+                    return Err(MarkupError::UnexpectedClose { column });
+                }
+                tokens.push(Token::Close);
+            }
+            ch => push_text(&mut tokens, ch),
+        }
+    }
+    if let Some((rule, column)) = open.pop() {
+        return Err(MarkupError::UnclosedRule { rule, column });
+    }
+    Ok(tokens)
+}
+
+fn push_text(tokens: &mut Vec<Token>, ch: char) {
+    if let Some(Token::Text(text)) = tokens.last_mut() {
+        text.push(ch);
+        return;
+    }
+    tokens.push(Token::Text(ch.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Token {
+        Token::Text(text.to_owned())
+    }
+
+    #[test]
+    fn conditions() {
+        assert_eq!(
+            tokenize("<if:(money() >= 20)>Buy it"),
+            Ok(vec![Token::Condition(parse("(money() >= 20)").unwrap()), text("Buy it")]),
+        );
+        assert_eq!(
+            tokenize(r#"<if:has_item(">")>x"#),
+            Ok(vec![Token::Condition(parse(r#"has_item(">")"#).unwrap()), text("x")]),
+        );
+        assert_eq!(
+            tokenize(r#"<if:a == "\">">y"#),
+            Ok(vec![Token::Condition(parse(r#"a == "\">""#).unwrap()), text("y")]),
+        );
+        assert_eq!(tokenize("Hi <if:x>"), Err(MarkupError::MisplacedCondition { column: 4 }));
+        assert_eq!(tokenize("<if:(a > b"), Err(MarkupError::UnclosedRule { rule: "if".to_owned(), column: 1 }));
+        assert_eq!(
+            tokenize("<if:1 2>x"),
+            Err(MarkupError::InvalidCondition {
+                column: 1,
+                error: ConditionError::Unexpected { found: "2".to_owned(), column: 3 },
+            }),
+        );
+    }
+
+    #[test]
+    fn pauses() {
+        assert_eq!(tokenize("Wait<pause:30> for it"), Ok(vec![text("Wait"), Token::Pause(30), text(" for it")]));
+        assert_eq!(tokenize("<pause: 15 >"), Ok(vec![Token::Pause(15)]));
+        assert_eq!(
+            tokenize("<b:a<pause:1>b>"),
+            Ok(vec![Token::Open("b".to_owned(), 1), text("a"), Token::Pause(1), text("b"), Token::Close]),
+        );
+        assert_eq!(tokenize("So <pause:soon>"), Err(MarkupError::InvalidPause { column: 4 }));
+        assert_eq!(tokenize("<pause:3"), Err(MarkupError::InvalidPause { column: 1 }));
+    }
+}
@@ -1,16 +1,58 @@
+use super::condition::{Context, Expression};
+use super::markup::{split_speaker, split_expression, tokenize, Token, MarkupError};
 use super::pretty_string::PrettyString;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Message {
     speaker: Option<String>,
+    expression: Option<String>,
+    condition: Option<Expression>,
     message: PrettyString,
 }
 
 impl Message {
+    pub fn parse(string: &str) -> Result<Self, MarkupError> {
+        let (speaker, message) = split_speaker(string);
+        let (speaker, expression) = match speaker.map(split_expression) {
+            Some((speaker, expression)) => (Some(speaker), expression),
+            None => (None, None),
+        };
+        let mut tokens = tokenize(message)?;
+        let condition = match tokens.first() {
+            Some(Token::Condition(..)) => match tokens.remove(0) {
+                Token::Condition(condition) => Some(condition),
+                _ => unreachable!(),
+            },
+            _ => None,
+        };
+        Ok(Message {
+            speaker: speaker.map(str::to_owned),
+            expression: expression.map(str::to_owned),
+            condition,
+            message: PrettyString::from_tokens(tokens)?,
+        })
+    }
+
     pub fn speaker(&self) -> &Option<String> {
         &self.speaker
     }
 
+    pub fn expression(&self) -> &Option<String> {
+        &self.expression
+    }
+
+    pub fn condition(&self) -> &Option<Expression> {
+        &self.condition
+    }
+
+    /// Whether the line or choice should be shown, which it always is if it has no condition.
+    pub fn is_shown(&self, context: &dyn Context) -> bool {
+        self.condition
+            .as_ref()
+            .map(|condition| condition.evaluate(context).is_true())
+            .unwrap_or(true)
+    }
+
     pub fn message(&self) -> &PrettyString {
         &self.message
     }
@@ -32,18 +74,23 @@ impl From<&String> for Message {
     }
 }
 
+/// Dialogs are checked by the build script, which reports any invalid markup, so it should always be
+/// valid here. If it is not, the line is shown as it was written rather than crashing the game.
+/// Nothing is reported, as lines are parsed again every frame.
 impl From<&str> for Message {
     fn from(string: &str) -> Self {
-        if let Some(index) = string.find(":") {
-            Message {
-                speaker: Some(string[..index].trim().to_string()),
-                message: PrettyString::parse(&string[index + 1..].trim()),
-            }
-        } else {
+        Message::parse(string).unwrap_or_else(|_| {
+            let (speaker, message) = split_speaker(string);
+            let (speaker, expression) = match speaker.map(split_expression) {
+                Some((speaker, expression)) => (Some(speaker), expression),
+                None => (None, None),
+            };
             Message {
-                speaker: None,
-                message: PrettyString::parse(&string[..]),
+                speaker: speaker.map(str::to_owned),
+                expression: expression.map(str::to_owned),
+                condition: None,
+                message: PrettyString::plain_text(message),
             }
-        }
+        })
     }
 }
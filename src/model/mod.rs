@@ -1,6 +1,14 @@
+pub mod command;
+pub mod condition;
 pub mod cutscene;
+pub mod dialog_event;
 pub mod direction;
 pub mod item;
+pub mod markup;
 pub mod message;
 pub mod money;
 pub mod pretty_string;
+pub mod speaker;
+pub mod sprite_animation;
+pub mod tile_animation;
+pub mod tile_properties;
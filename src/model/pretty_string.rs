@@ -1,157 +1,141 @@
 use std::collections::HashMap;
 use game_engine::prelude::*;
 use lazy_static::lazy_static;
-use crate::font::default;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::font;
+use super::markup::{tokenize, Token, MarkupError};
 
-lazy_static! {
-    static ref RULES: HashMap<&'static str, Attributes> = {
-        let mut map = HashMap::new();
-        map.insert("location", Attributes { font: None, color: Some(Color::BLUE) });
-        map.insert("thought", Attributes { font: Some(&default::ITALIC_20), color: Some(0x555050ff.into()) });
-        map.insert("yell", Attributes { font: Some(&default::ITALIC_20), color: None });
-        map
-    };
-}
+// the rules are declared in `dialog/rules.toml`
+include!(concat!(env!("OUT_DIR"), "/rules.rs"));
+
+/// The font of text that has not been given a family, style, or size by its rules.
+const DEFAULT_FAMILY: &str = "default";
+const DEFAULT_STYLE: &str = "regular";
+const DEFAULT_SIZE: u32 = 20;
 
+/// The attributes of text, as set by its rules. Colours are kept as `0xrrggbbaa` so that the
+/// alpha can be changed as the text fades in.
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub struct Attributes {
-    pub font: Option<&'static Font>,
-    pub color: Option<Color>,
+    pub family: Option<&'static str>,
+    pub style: Option<&'static str>,
+    pub size: Option<u32>,
+    pub color: Option<u32>,
+    pub underline: Option<bool>,
+    pub outline: Option<u32>,
+    pub background: Option<u32>,
+    /// How far, in pixels, each character jitters around.
+    pub shake: Option<u32>,
+    /// How high, in pixels, the characters bob up and down in a wave.
+    pub wave: Option<u32>,
+    /// How many frames each character takes to fade in once it is shown.
+    pub fade: Option<u32>,
 }
 
 impl Attributes {
     pub fn override_with(self, other: &Attributes) -> Attributes {
         Attributes {
-            font: other.font.or(self.font),
+            family: other.family.or(self.family),
+            style: other.style.or(self.style),
+            size: other.size.or(self.size),
             color: other.color.or(self.color),
+            underline: other.underline.or(self.underline),
+            outline: other.outline.or(self.outline),
+            background: other.background.or(self.background),
+            shake: other.shake.or(self.shake),
+            wave: other.wave.or(self.wave),
+            fade: other.fade.or(self.fade),
         }
     }
-}
 
-#[derive(Clone, Eq, PartialEq, Default, Debug)]
-pub struct PrettyString(pub Vec<(String, Attributes)>);
-
-impl PrettyString {
-    pub fn parse(string: &str) -> Self{
-        parser::parse(string)
+    /// The font to draw the text with, if the rules call for something other than the default.
+    pub fn font(&self) -> Option<&'static Font> {
+        if self.family.is_none() && self.style.is_none() && self.size.is_none() {
+            return None;
+        }
+        font::find(
+            self.family.unwrap_or(DEFAULT_FAMILY),
+            self.style.unwrap_or(DEFAULT_STYLE),
+            self.size.unwrap_or(DEFAULT_SIZE),
+        )
     }
 
-    pub fn len(&self) -> usize {
-        self.0
-            .iter()
-            .map(|(string, _)| string.len())
-            .fold(0, |a, b| a + b)
+    pub fn is_underlined(&self) -> bool {
+        self.underline.unwrap_or(false)
     }
 
-    pub fn plain(&self) -> String {
-        self.0.iter()
-            .map(|(string, _)| string.clone())
-            .collect()
+    /// Whether the text has to be drawn one character at a time.
+    pub fn has_effects(&self) -> bool {
+        self.shake.is_some() || self.wave.is_some() || self.fade.is_some()
     }
 }
 
-mod parser {
-    use super::{PrettyString, Attributes, RULES};
-    use std::ops::{Generator, GeneratorState};
-
-    enum State {
-        Start,
-        Open(String),
-        Close,
-    }
-
-    pub enum Token {
-        StartSegment(String),
-        EndSegment,
-        Text(String),
-    }
-
-    fn lmm(text: &str, state: State) -> (Token, &str) {
-        use self::State::*;
-        let mut chars = text.chars();
-        match (state, chars.next()) {
-            (Start, Some('<')) => lmm(chars.as_str(), Open("".to_owned())),
-            (Start, Some('>')) => lmm(chars.as_str(), Close),
-            (Start, Some(ch)) => (Token::Text(ch.to_string()), chars.as_str()),
-            (Open(ref st), Some('<')) if st.is_empty() => (Token::Text("<".to_owned()), text),
-            (Open(ref st), Some('>')) if st.is_empty() => (Token::Text(">".to_owned()), text),
-            (Open(ref st), Some(':')) if st.is_empty() => panic!("Missing rule name in dialog string"),
-            (Open(st), Some(':')) => (Token::StartSegment(st), chars.as_str()),
-            (Open(st), Some(ch)) => lmm(chars.as_str(), Open(st + &ch.to_string())),
-            (Close, _) => (Token::EndSegment, text),
-            _ => panic!("Unexpected end of string"),
-        }
-    }
-
-    fn tokenize(mut string: &'a str) -> impl Iterator<Item = Token> + 'a {
-        let gen = move || {
-            while !string.is_empty() {
-                let (token, rest) = lmm(string, State::Start);
-                yield token;
-                string = rest;
-            }
-        };
-        generator_to_iterator(gen)
-    }
+/// Text split into segments by their attributes, along with the pauses in it, each of which is
+/// the number of glyphs (grapheme clusters) before it and the number of frames it lasts.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct PrettyString(pub Vec<(String, Attributes)>, Vec<(usize, u32)>);
 
-    fn resolve_rules(rules: &[String]) -> Attributes {
-        rules.iter()
-            .fold(
-                Attributes::default(),
-                |attrs, rule| attrs.override_with(
-                    RULES
-                        .get(rule.as_str())
-                        .expect(&format!("Missing rule {} in dialog string", rule))
-                )
-            )
+impl PrettyString {
+    pub fn parse(string: &str) -> Result<Self, MarkupError> {
+        Self::from_tokens(tokenize(string)?)
     }
 
-    pub(super) fn parse(string: &str) -> PrettyString {
-        let mut segments = vec![];
-        let mut rules = vec![];
-        let mut segment = String::new();
-        for token in tokenize(string) {
+    /// Applies the rules to the text that they are around. A condition is not part of the text, so
+    /// it is left for the `Message` to deal with.
+    pub fn from_tokens(tokens: Vec<Token>) -> Result<Self, MarkupError> {
+        let mut segments: Vec<(String, Attributes)> = vec![];
+        let mut pauses = vec![];
+        let mut rules: Vec<&Attributes> = vec![];
+        for token in tokens {
             match token {
-                Token::StartSegment(name) => {
-                    if !segment.is_empty() {
-                        segments.push((segment, resolve_rules(&rules)));
-                        segment = String::new();
-                    }
-                    rules.push(name);
+                Token::Open(name, column) => {
+                    let rule = RULES
+                        .get(name.as_str())
+                        .ok_or_else(|| MarkupError::UnknownRule { rule: name.clone(), column })?;
+                    rules.push(rule);
+                }
+                Token::Close => { rules.pop(); }
+                Token::Text(text) => {
+                    let attributes = rules
+                        .iter()
+                        .fold(Attributes::default(), |attributes, rule| attributes.override_with(rule));
+                    segments.push((text, attributes));
                 }
-                Token::EndSegment => {
-                    if !segment.is_empty() {
-                        segments.push((segment, resolve_rules(&rules)));
-                        segment = String::new();
-                    }
-                    rules.pop().expect("Ran out of rules to pop");
+                Token::Pause(frames) => {
+                    let position = segments.iter().map(|(text, _)| text.graphemes(true).count()).sum();
+                    pauses.push((position, frames));
                 }
-                Token::Text(text) => segment = segment + &text,
+                Token::Condition(..) => {}
             }
         }
-        if !segment.is_empty() {
-            segments.push((segment, resolve_rules(&rules)));
-        }
-        PrettyString(segments)
+        Ok(PrettyString(segments, pauses))
     }
 
-    fn generator_to_iterator<G>(g: G) -> impl Iterator<Item = G::Yield>
-    where G: Generator<Return = ()> {
-        struct It<G>(G);
+    /// Text that is shown as is, without any rules applied.
+    pub fn plain_text(string: &str) -> Self {
+        PrettyString(vec![(string.to_owned(), Attributes::default())], vec![])
+    }
 
-        impl<G: Generator<Return = ()>> Iterator for It<G> {
-            type Item = G::Yield;
+    /// Text made of segments that already have their attributes, like a speaker's name in bold.
+    pub fn from_segments(segments: Vec<(String, Attributes)>) -> Self {
+        PrettyString(segments, vec![])
+    }
 
-            fn next(&mut self) -> Option<Self::Item> {
-                unsafe {
-                    match self.0.resume() {
-                        GeneratorState::Yielded(y) => Some(y),
-                        GeneratorState::Complete(()) => None,
-                    }
-                }
-            }
-        }
+    pub fn pauses(&self) -> &[(usize, u32)] {
+        &self.1
+    }
 
-        It(g)
+    /// The number of glyphs (grapheme clusters) in the text.
+    pub fn len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(string, _)| string.graphemes(true).count())
+            .fold(0, |a, b| a + b)
+    }
+
+    pub fn plain(&self) -> String {
+        self.0.iter()
+            .map(|(string, _)| string.clone())
+            .collect()
     }
 }
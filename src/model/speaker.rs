@@ -0,0 +1,40 @@
+use game_engine::prelude::*;
+use crate::sprite;
+
+// the speakers are declared in `dialog/speakers.toml`
+include!(concat!(env!("OUT_DIR"), "/speakers.rs"));
+
+/// The expression shown when a line does not pick one.
+const NEUTRAL: &str = "neutral";
+
+/// A speaker that has a portrait, with the frame of the portrait sprite for each expression.
+#[derive(Debug)]
+pub struct Speaker {
+    pub name: &'static str,
+    pub portrait: &'static Sprite,
+    expressions: &'static [(&'static str, usize)],
+}
+
+impl Speaker {
+    pub const fn new(name: &'static str, portrait: &'static Sprite, expressions: &'static [(&'static str, usize)]) -> Self {
+        Speaker { name, portrait, expressions }
+    }
+
+    /// Finds the speaker by the name that their lines start with.
+    pub fn find(name: &str) -> Option<&'static Speaker> {
+        SPEAKERS.iter().find(|speaker| speaker.name == name)
+    }
+
+    /// The frame of the portrait for an expression, or for the neutral one if there is no such
+    /// expression.
+    pub fn frame(&self, expression: Option<&str>) -> usize {
+        let frame = |name: &str| self.expressions
+            .iter()
+            .find(|(expression, _)| *expression == name)
+            .map(|(_, frame)| *frame);
+        expression
+            .and_then(frame)
+            .or_else(|| frame(NEUTRAL))
+            .unwrap_or(0)
+    }
+}
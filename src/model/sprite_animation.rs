@@ -0,0 +1,20 @@
+use std::ops::Range;
+
+/// A named animation of a sprite, as defined in its TOML. The `frames` are played in a loop while
+/// the animation is running, and the `idle` frame is shown when it stops.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SpriteAnimation {
+    pub idle: usize,
+    pub frames: Range<usize>,
+}
+
+impl SpriteAnimation {
+    pub const fn new(idle: usize, frames: Range<usize>) -> Self {
+        SpriteAnimation { idle, frames }
+    }
+
+    /// Whether the frame is part of this animation, including its idle frame.
+    pub fn contains(&self, frame: usize) -> bool {
+        frame == self.idle || self.frames.contains(&frame)
+    }
+}
@@ -0,0 +1,39 @@
+/// One frame of an animated tile, showing the tile at `index` of the same tile set for `duration`
+/// milliseconds.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TileFrame {
+    pub index: usize,
+    pub duration: u32,
+}
+
+impl TileFrame {
+    pub const fn new(index: usize, duration: u32) -> Self {
+        TileFrame { index, duration }
+    }
+}
+
+/// The animation of a tile, as defined in its Tiled tile set. The total duration is computed by
+/// the build script, so that these can all be constants.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TileAnimation {
+    frames: &'static [TileFrame],
+    duration: u32,
+}
+
+impl TileAnimation {
+    pub const fn new(frames: &'static [TileFrame], duration: u32) -> Self {
+        TileAnimation { frames, duration }
+    }
+
+    /// The index of the tile to show once the animation has been running for `time` milliseconds.
+    pub fn frame_at(&self, time: u64) -> usize {
+        let mut time = (time % self.duration as u64) as u32;
+        for frame in self.frames {
+            if time < frame.duration {
+                return frame.index;
+            }
+            time -= frame.duration;
+        }
+        self.frames[0].index
+    }
+}
@@ -0,0 +1,57 @@
+use game_engine::prelude::*;
+
+/// The custom properties of a tile, as defined in its Tiled tile set.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TileProperties {
+    properties: &'static [(&'static str, &'static str)],
+}
+
+impl TileProperties {
+    pub const fn new(properties: &'static [(&'static str, &'static str)]) -> Self {
+        TileProperties { properties }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'static str> {
+        self.properties
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// The tiles of one layer of a map that have custom properties, by their index in the layer's
+/// grid. Only tiles with properties are listed, in order of index.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TilePropertyLayer {
+    columns: usize,
+    tile_width: u32,
+    tile_height: u32,
+    tiles: &'static [(usize, &'static TileProperties)],
+}
+
+impl TilePropertyLayer {
+    pub const fn new(columns: usize, tile_width: u32, tile_height: u32, tiles: &'static [(usize, &'static TileProperties)]) -> Self {
+        TilePropertyLayer { columns, tile_width, tile_height, tiles }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The properties of the tile under a point in the world, if it has any.
+    pub fn at(&self, point: Point<f32>) -> Option<&'static TileProperties> {
+        if point.x < 0f32 || point.y < 0f32 {
+            return None;
+        }
+        let column = (point.x / self.tile_width as f32) as usize;
+        let row = (point.y / self.tile_height as f32) as usize;
+        if column >= self.columns {
+            return None;
+        }
+        let index = row * self.columns + column;
+        self.tiles
+            .binary_search_by_key(&index, |(index, _)| *index)
+            .ok()
+            .map(|found| self.tiles[found].1)
+    }
+}
@@ -115,6 +115,51 @@ pub(super) fn process_control_events(world: &mut World) {
         }
     }
 
+    match control_scheme.backlog {
+        Control::Key(key) => {
+            control_state.backlog = keyboard_state.key_pressed(key);
+        }
+        Control::MouseButton(MouseButton::Left) => {
+            control_state.backlog = mouse_state.left_pressed();
+        }
+        Control::MouseButton(MouseButton::Right) => {
+            control_state.backlog = mouse_state.right_pressed();
+        }
+        Control::MouseButton(MouseButton::Middle) => {
+            control_state.backlog = mouse_state.middle_pressed();
+        }
+    }
+
+    match control_scheme.auto {
+        Control::Key(key) => {
+            control_state.auto = keyboard_state.key_pressed(key);
+        }
+        Control::MouseButton(MouseButton::Left) => {
+            control_state.auto = mouse_state.left_pressed();
+        }
+        Control::MouseButton(MouseButton::Right) => {
+            control_state.auto = mouse_state.right_pressed();
+        }
+        Control::MouseButton(MouseButton::Middle) => {
+            control_state.auto = mouse_state.middle_pressed();
+        }
+    }
+
+    match control_scheme.fast_forward {
+        Control::Key(key) => {
+            control_state.fast_forward = keyboard_state.key_pressed(key);
+        }
+        Control::MouseButton(MouseButton::Left) => {
+            control_state.fast_forward = mouse_state.left_pressed();
+        }
+        Control::MouseButton(MouseButton::Right) => {
+            control_state.fast_forward = mouse_state.right_pressed();
+        }
+        Control::MouseButton(MouseButton::Middle) => {
+            control_state.fast_forward = mouse_state.middle_pressed();
+        }
+    }
+
     for keyboard_event in keyboard_events.iter() {
         if let KeyboardEvent::Press(key) = keyboard_event {
             if Control::Key(key) == control_scheme.dir_left {
@@ -141,6 +186,15 @@ pub(super) fn process_control_events(world: &mut World) {
             if Control::Key(key) == control_scheme.run {
                 control_events.add(ControlEvent::Run(None));
             }
+            if Control::Key(key) == control_scheme.backlog {
+                control_events.add(ControlEvent::Backlog(None));
+            }
+            if Control::Key(key) == control_scheme.auto {
+                control_events.add(ControlEvent::Auto(None));
+            }
+            if Control::Key(key) == control_scheme.fast_forward {
+                control_events.add(ControlEvent::FastForward(None));
+            }
         }
     }
 
@@ -158,6 +212,15 @@ pub(super) fn process_control_events(world: &mut World) {
             if Control::MouseButton(button) == control_scheme.run {
                 control_events.add(ControlEvent::Run(Some(position)));
             }
+            if Control::MouseButton(button) == control_scheme.backlog {
+                control_events.add(ControlEvent::Backlog(Some(position)));
+            }
+            if Control::MouseButton(button) == control_scheme.auto {
+                control_events.add(ControlEvent::Auto(Some(position)));
+            }
+            if Control::MouseButton(button) == control_scheme.fast_forward {
+                control_events.add(ControlEvent::FastForward(Some(position)));
+            }
         }
     }
 }
@@ -0,0 +1,123 @@
+use game_engine::prelude::*;
+use crate::resource::{
+    control::{
+        Control,
+        ControlEvent,
+        ControlEvents,
+        ControlScheme,
+        ControlState,
+        ControlsMenu,
+        CONTROL_NAMES,
+        DIRECTIONS,
+    },
+    dialog::DialogBacklog,
+};
+
+/// Runs the controls menu, which the menu control opens. While it is open it takes all of the
+/// input, so the rest of the game sees nothing pressed.
+pub(super) fn run_controls_menu(world: &mut World) {
+    let mut control_events = world.write_resource::<ControlEvents>();
+    let mut control_state = world.write_resource::<ControlState>();
+    let mut controls_menu = world.write_resource::<ControlsMenu>();
+    let mut control_scheme = world.write_resource::<ControlScheme>();
+    let keyboard_events = world.read_resource::<KeyboardEvents>();
+    let mouse_events = world.read_resource::<MouseEvents>();
+    let dialog_backlog = world.read_resource::<DialogBacklog>();
+
+    let escaped = keyboard_events
+        .iter()
+        .any(|event| if let KeyboardEvent::Press(Key::Escape) = event { true } else { false });
+    if !controls_menu.is_open() {
+        let opened = !dialog_backlog.is_open() && control_events
+            .iter()
+            .any(|event| if let ControlEvent::Menu(..) = event { true } else { false });
+        if !opened {
+            return;
+        }
+        controls_menu.toggle();
+    } else if controls_menu.is_capturing() {
+        // the first key or button pressed is the one to bind, except for escape, which gives up
+        let pressed = keyboard_events
+            .iter()
+            .filter_map(|event| if let KeyboardEvent::Press(key) = event { Some(Control::Key(key)) } else { None })
+            .chain(mouse_events
+                .iter()
+                .filter_map(|event| if let MouseEvent::Press(button, _) = event { Some(Control::MouseButton(button)) } else { None }))
+            .next();
+        if let Some(control) = pressed {
+            controls_menu.stop_capturing();
+            if !escaped {
+                bind(&mut controls_menu, &mut control_scheme, control);
+            }
+        }
+    } else if escaped {
+        // escape always closes the menu, in case the controls have been bound to something awkward
+        controls_menu.toggle();
+    } else {
+        for event in control_events.iter() {
+            match event {
+                | ControlEvent::Menu(..)
+                | ControlEvent::Cancel(..) => {
+                    controls_menu.toggle();
+                    break;
+                }
+                ControlEvent::Up(..) => controls_menu.up(),
+                ControlEvent::Down(..) => controls_menu.down(),
+                ControlEvent::Action(..) => {
+                    // the key that was just pressed is not the one to bind, so the next one is
+                    controls_menu.start_capturing();
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    control_events.clear();
+    *control_state = ControlState::default();
+}
+
+/// Binds the input to the selected control, if it can be, and saves the controls.
+fn bind(controls_menu: &mut ControlsMenu, control_scheme: &mut ControlScheme, control: Control) {
+    let selected = controls_menu.selected();
+    let name = match control.name() {
+        Some(name) => name,
+        None => {
+            controls_menu.set_message(String::from("That key cannot be used as a control"));
+            return;
+        }
+    };
+    let previous = control_scheme.controls()[selected];
+    let conflict = control_scheme.conflict(selected, control);
+
+    // the directions are held down rather than pressed, which only works for keys
+    let is_button = |control: Control| if let Control::MouseButton(..) = control { true } else { false };
+    if selected < DIRECTIONS && is_button(control) {
+        controls_menu.set_message(format!("{} can only be a key", CONTROL_NAMES[selected]));
+        return;
+    }
+    if let Some(other) = conflict {
+        if other < DIRECTIONS && is_button(previous) {
+            controls_menu.set_message(format!(
+                "{} is used for {}, which can only be a key",
+                name,
+                CONTROL_NAMES[other],
+            ));
+            return;
+        }
+    }
+
+    match control_scheme.bind(selected, control) {
+        Some(other) => controls_menu.set_message(format!(
+            "{} was used for {}, which now uses {}",
+            name,
+            CONTROL_NAMES[other],
+            previous.name().unwrap_or_else(|| String::from("nothing")),
+        )),
+        None => controls_menu.set_message(format!("{} now uses {}", CONTROL_NAMES[selected], name)),
+    }
+    if let Err(error) = control_scheme.save() {
+        eprintln!("Could not save the controls: {}", error);
+        controls_menu.set_message(String::from("The controls could not be saved"));
+    }
+}
@@ -1,52 +1,300 @@
 use game_engine::prelude::*;
+use ron::de::from_str;
+use crate::component::{
+    marker::Player,
+    inventory::{Inventory, Wallet},
+};
+use crate::model::{
+    command,
+    condition::{Context, Value},
+    message::Message,
+};
 use crate::resource::{
-    dialog::{DialogSpeed, DialogProgress, DialogMessages, DialogSelection, DialogEvents},
-    control::{ControlEvents, ControlEvent},
+    dialog::{
+        DialogSpeed,
+        DialogProgress,
+        DialogPage,
+        DialogMessages,
+        DialogSelection,
+        DialogEvents,
+        DialogLayout,
+        DialogHistory,
+        DialogBacklog,
+        DialogVariables,
+        DialogCommands,
+        DialogMode,
+        DialogSeen,
+        DialogTimeout,
+    },
+    control::{ControlEvents, ControlEvent, ControlState, ControlsMenu},
+    state::State,
 };
 
+/// What the dialogs can see of the game, for their conditions and the values of their variables.
+pub(super) struct DialogContext<'a> {
+    pub(super) variables: &'a DialogVariables,
+    pub(super) state: &'a State,
+    pub(super) inventory: Option<&'a Inventory>,
+    pub(super) wallet: Option<&'a Wallet>,
+}
+
+impl Context for DialogContext<'_> {
+    fn variable(&self, name: &str) -> Value {
+        self.variables.get(name)
+    }
+
+    fn call(&self, function: &str, arguments: &[Value]) -> Value {
+        match (function, arguments) {
+            ("has_item", [Value::Text(item)]) => Value::Bool(self.inventory.map(|inventory| inventory.count_named(item) > 0).unwrap_or(false)),
+            ("money", []) => Value::Number(self.wallet.map(|wallet| wallet.amount().0 as i64).unwrap_or(0)),
+            ("state_is", [Value::Text(state)]) => Value::Bool(state.parse().map(|state| self.state.is(state)).unwrap_or(false)),
+            _ => {
+                eprintln!("Dialog called {} with {:?}, which it cannot be", function, arguments);
+                Value::default()
+            }
+        }
+    }
+}
+
+/// Moves past the lines whose conditions are false. A line with choices is always shown, as there
+/// is no moving past it without picking one.
+fn skip_hidden(dialog_messages: &mut DialogMessages, context: &dyn Context) {
+    while let Some(paragraph) = dialog_messages.current() {
+        if paragraph.choices().is_some() || Message::from(paragraph.text()).is_shown(context) {
+            break;
+        }
+        dialog_messages.next();
+    }
+}
+
+/// Runs the tags of the current paragraph. Commands are left for `run_dialog_commands`, and the
+/// rest of the tags are parsed as dialog events for the cutscenes.
+fn run_tags(dialog_messages: &mut DialogMessages, dialog_events: &mut DialogEvents, dialog_commands: &mut DialogCommands) {
+    let story = dialog_messages.story();
+    if let Some(current) = dialog_messages.current_mut() {
+        for tag in current.take_tags().into_iter() {
+            match command::parse(&tag) {
+                Some(Ok(command)) => dialog_commands.add(command),
+                Some(Err(error)) => eprintln!("Invalid command in dialog tag {:?}: {}", tag, error),
+                None => match from_str(&tag) {
+                    Ok(event) => dialog_events.add(story, event),
+                    Err(error) => eprintln!("Skipping unknown dialog event {:?}: {}", tag, error),
+                },
+            }
+        }
+    }
+}
+
+/// The player, whose inventory and wallet the dialogs look at.
+pub(super) fn find_player(world: &World) -> Option<Entity> {
+    let entities = world.entities();
+    let player = world.read_storage::<Player>();
+    let found = (&*entities, &player).join().next().map(|(entity, _)| entity);
+    found
+}
+
 pub(super) fn manage_dialog(world: &mut World) {
+    // the dialog waits while the controls menu is open, so that time does not run out on a choice
+    // and auto mode does not move on while the player is looking at the controls
+    if world.read_resource::<ControlsMenu>().is_open() {
+        return;
+    }
+
     let control_events = world.read_resource::<ControlEvents>();
     let mut dialog_progress = world.write_resource::<DialogProgress>();
+    let mut dialog_page = world.write_resource::<DialogPage>();
     let mut dialog_messages = world.write_resource::<DialogMessages>();
     let mut dialog_events = world.write_resource::<DialogEvents>();
     let mut dialog_selection = world.write_resource::<DialogSelection>();
     let dialog_speed = world.read_resource::<DialogSpeed>();
+    let dialog_layout = world.read_resource::<DialogLayout>();
+    let mut dialog_history = world.write_resource::<DialogHistory>();
+    let mut dialog_backlog = world.write_resource::<DialogBacklog>();
+    let mut dialog_commands = world.write_resource::<DialogCommands>();
+    let mut dialog_mode = world.write_resource::<DialogMode>();
+    let mut dialog_seen = world.write_resource::<DialogSeen>();
+    let mut dialog_timeout = world.write_resource::<DialogTimeout>();
+    let control_state = world.read_resource::<ControlState>();
+    let dialog_variables = world.read_resource::<DialogVariables>();
+    let mouse_events = world.read_resource::<MouseEvents>();
+    let state = world.read_resource::<State>();
+    let inventories = world.read_storage::<Inventory>();
+    let wallets = world.read_storage::<Wallet>();
+    let player = find_player(world);
+    let context = DialogContext {
+        variables: &dialog_variables,
+        state: &state,
+        inventory: player.and_then(|player| inventories.get(player)),
+        wallet: player.and_then(|player| wallets.get(player)),
+    };
+
+    dialog_mode.set_fast_forward(control_state.fast_forward);
+    for event in control_events.iter() {
+        if let ControlEvent::Auto(..) = event {
+            dialog_mode.toggle_auto();
+        }
+    }
 
-    dialog_events.clear();
+    // while the backlog is open, the controls are for scrolling it instead of the dialog
+    let backlog_was_open = dialog_backlog.is_open();
+    for event in control_events.iter() {
+        match event {
+            ControlEvent::Backlog(..) => dialog_backlog.toggle(),
+            ControlEvent::Cancel(..) if dialog_backlog.is_open() => dialog_backlog.close(),
+            ControlEvent::Up(..) if dialog_backlog.is_open() => dialog_backlog.scroll_back(dialog_history.len()),
+            ControlEvent::Down(..) if dialog_backlog.is_open() => dialog_backlog.scroll_forward(),
+            _ => {}
+        }
+    }
+    if backlog_was_open || dialog_backlog.is_open() {
+        return;
+    }
+
+    // a story may have started since the last frame, so its first line is looked at here too. The
+    // tags are taken as they run, so each of them only runs once
+    skip_hidden(&mut dialog_messages, &context);
+    run_tags(&mut dialog_messages, &mut dialog_events, &mut dialog_commands);
     let paragraph = dialog_messages.current().cloned();
     if let Some(paragraph) = paragraph {
+        // choices whose conditions are false are left out
+        if let Some(choices) = paragraph.choices() {
+            let shown: Vec<_> = choices
+                .iter()
+                .enumerate()
+                .filter(|(_, choice)| Message::from(*choice).is_shown(&context))
+                .map(|(index, _)| index)
+                .collect();
+            if shown != dialog_selection.choices() {
+                dialog_selection.set_up(shown);
+            }
+        }
+
+        // the typewriter runs over one page at a time, once the drawable has worked out the pages
+        let pages = dialog_layout.pages(&paragraph.text());
+        let page = dialog_page.current();
+        let is_last_page = pages.as_ref().map(|pages| page + 1 >= pages.len()).unwrap_or(true);
         if dialog_progress.current().is_some() {
-            dialog_progress.progress(dialog_speed.0, paragraph.text().len());
+            let message = Message::from(paragraph.text());
+            let start = pages.as_ref().map(|pages| pages.iter().take(page).sum()).unwrap_or(0);
+            let limit = pages
+                .as_ref()
+                .and_then(|pages| pages.get(page).cloned())
+                .unwrap_or_else(|| message.len());
+            let pauses: Vec<_> = message.message()
+                .pauses()
+                .iter()
+                .filter(|(position, _)| *position >= start)
+                .map(|(position, frames)| (position - start, *frames))
+                .collect();
+            dialog_progress.progress(dialog_speed.0, limit, &pauses);
+        }
+
+        // hovering over a choice only selects it when the mouse moves, so that it does not fight
+        // the keyboard
+        let pointer = mouse_events
+            .iter()
+            .filter_map(|event| if let MouseEvent::Move(point) = event { Some(point) } else { None })
+            .last();
+        if let Some(index) = pointer.and_then(|pointer| dialog_layout.choice_at(pointer)) {
+            dialog_selection.select(index);
         }
 
-        for event in control_events.iter() {
+        // the auto and fast forward modes move the dialog along as if the action button was
+        // pressed, but they leave the choices for the player, unless the choices run out of time
+        let page_len = pages
+            .as_ref()
+            .and_then(|pages| pages.get(page).cloned())
+            .unwrap_or_else(|| Message::from(paragraph.text()).len());
+        let awaiting_choice = is_last_page && paragraph.choices().is_some() && dialog_progress.current().is_none();
+        let timed_out = awaiting_choice && dialog_timeout.tick();
+        if timed_out {
+            let choice = dialog_timeout.pick(dialog_selection.choices());
+            dialog_selection.select(choice);
+        }
+        let moves_on = if awaiting_choice {
+            dialog_mode.stop_waiting();
+            timed_out
+        } else if dialog_mode.is_fast_forwarding() && dialog_seen.has_seen(&paragraph.text()) {
+            true
+        } else if dialog_progress.current().is_none() {
+            dialog_mode.wait(page_len)
+        } else {
+            dialog_mode.stop_waiting();
+            false
+        };
+        // once time has run out, the player does not get to change the choice
+        let mut events: Vec<_> = control_events
+            .iter()
+            .cloned()
+            .filter(|_| !timed_out)
+            .collect();
+        // if the player moved the dialog along themselves, it must not move along twice
+        let advanced = events
+            .iter()
+            .any(|event| match event {
+                ControlEvent::Action(..) | ControlEvent::Cancel(..) => true,
+                _ => false,
+            });
+        if moves_on && !advanced {
+            events.push(ControlEvent::Action(None));
+        }
+
+        for event in events.iter() {
             match event {
                 ControlEvent::Down(..) => dialog_selection.down(),
-                ControlEvent::Up(..) => dialog_selection.down(),
+                ControlEvent::Up(..) => dialog_selection.up(),
 
-                | ControlEvent::Action(..)
-                | ControlEvent::Cancel(..) => {
+                | ControlEvent::Action(point)
+                | ControlEvent::Cancel(point) => {
+                    if let Some(point) = *point {
+                        if dialog_progress.current().is_none() && is_last_page && paragraph.choices().is_some() {
+                            // a click only picks a choice if it is on one
+                            match dialog_layout.choice_at(point) {
+                                Some(index) => dialog_selection.select(index),
+                                None => continue,
+                            }
+                        } else if dialog_progress.current().is_some() && !dialog_layout.dialog_box_contains(point) {
+                            continue;
+                        }
+                    }
+                    dialog_mode.stop_waiting();
                     if dialog_progress.current().is_some() {
                         dialog_progress.skip();
+                    } else if !is_last_page {
+                        dialog_page.next();
+                        dialog_progress.reset();
                     } else {
+                        dialog_page.reset();
                         dialog_progress.reset();
+                        dialog_seen.mark(paragraph.text());
                         if paragraph.choices().is_some() {
+                            let choice = paragraph.choices()
+                                .as_ref()
+                                .and_then(|choices| choices.get(dialog_selection.current() - 1))
+                                .cloned();
+                            if let Some(choice) = choice {
+                                dialog_history.choose(&choice);
+                            }
                             dialog_messages.select(dialog_selection.current())
                         } else {
                             dialog_messages.next()
                         };
-                        if let Some(current) = dialog_messages.current_mut() {
-                            if let Some(count) = current.choices().as_ref().map(Vec::len) {
-                                dialog_selection.set_up(count);
-                            }
-                            for tag in current.take_tags().into_iter() {
-                                dialog_events.add(tag);
-                            }
-                        }
+                        dialog_selection.set_up(vec![]);
+                        dialog_timeout.clear();
+                        skip_hidden(&mut dialog_messages, &context);
+                        run_tags(&mut dialog_messages, &mut dialog_events, &mut dialog_commands);
                     }
                 }
                 _ => {}
             }
         }
     }
+
+    match dialog_messages.current() {
+        Some(paragraph) => dialog_history.show(dialog_messages.number(), &paragraph.text()),
+        None => {
+            dialog_history.end();
+            dialog_timeout.clear();
+        }
+    }
 }
@@ -0,0 +1,148 @@
+use game_engine::prelude::*;
+use crate::constant::TILE_SIZE;
+use crate::component::{
+    behavior::MovePath,
+    graphics::{SpriteFrame, WalkCycle},
+    id::Id,
+    inventory::{Inventory, Wallet},
+    speech_bubble::SpeechBubble,
+};
+use crate::model::{
+    command::{Command, Facing},
+    direction::Direction,
+    item::Item,
+    money::Money,
+    pretty_string::PrettyString,
+};
+use crate::resource::{
+    dialog::{DialogCommands, DialogMessages, DialogTimeout, DialogVariables},
+    state::{State, MainState},
+};
+use super::dialog::{find_player, DialogContext};
+
+/// Runs the commands from the tags of the lines that were shown this frame.
+pub(super) fn run_dialog_commands(world: &mut World) {
+    let commands = world.write_resource::<DialogCommands>().take();
+    for command in commands {
+        if let Err(error) = run_command(world, &command) {
+            eprintln!("Could not run dialog command {:?}: {}", command, error);
+        }
+    }
+}
+
+fn run_command(world: &mut World, command: &Command) -> Result<(), String> {
+    match command {
+        Command::Set(name, value) => {
+            let value = {
+                let dialog_variables = world.read_resource::<DialogVariables>();
+                let state = world.read_resource::<State>();
+                let inventories = world.read_storage::<Inventory>();
+                let wallets = world.read_storage::<Wallet>();
+                let player = find_player(world);
+                let context = DialogContext {
+                    variables: &dialog_variables,
+                    state: &state,
+                    inventory: player.and_then(|player| inventories.get(player)),
+                    wallet: player.and_then(|player| wallets.get(player)),
+                };
+                value.evaluate(&context)
+            };
+            world.write_resource::<DialogVariables>().set(name.as_str(), value);
+        }
+        Command::Give(name, count) => {
+            let item = Item::find(name).ok_or_else(|| format!("there is no item named {:?}", name))?;
+            let player = find_player(world).ok_or("there is no player")?;
+            let mut inventories = world.write_storage::<Inventory>();
+            let inventory = inventories.get_mut(player).ok_or("the player has no inventory")?;
+            inventory.add(&item, *count);
+        }
+        Command::Take(name, count) => {
+            let item = Item::find(name).ok_or_else(|| format!("there is no item named {:?}", name))?;
+            let player = find_player(world).ok_or("there is no player")?;
+            let mut inventories = world.write_storage::<Inventory>();
+            let inventory = inventories.get_mut(player).ok_or("the player has no inventory")?;
+            let held = inventory.count(&item);
+            inventory.take(&item, u32::min(held, *count));
+            if held < *count {
+                return Err(format!("the player only had {} {}", held, name));
+            }
+        }
+        Command::Money(amount) => {
+            let player = find_player(world).ok_or("there is no player")?;
+            let mut wallets = world.write_storage::<Wallet>();
+            let wallet = wallets.get_mut(player).ok_or("the player has no wallet")?;
+            if *amount >= 0 {
+                *wallet += Money(*amount as u64);
+            } else {
+                let held = wallet.amount();
+                let amount = Money(amount.wrapping_neg() as u64);
+                *wallet -= Money(u64::min(held.0, amount.0));
+                if held < amount {
+                    return Err(format!("the player only had {} money", held.0));
+                }
+            }
+        }
+        Command::State(name) => {
+            let state: MainState = name.parse().map_err(|_| format!("there is no state named {:?}", name))?;
+            world.write_resource::<State>().enter(state);
+        }
+        Command::Move(target, path) => {
+            let target: Id = target.parse().map_err(|_| format!("there is no Id named {:?}", target))?;
+            let points: Vec<_> = path
+                .iter()
+                .map(|&(x, y)| Point::new(TILE_SIZE as f32 * x as f32, TILE_SIZE as f32 * y as f32))
+                .collect();
+            let entity = find(world, target)?;
+            world.write_storage::<MovePath>().insert(entity, MovePath::from(&points)).unwrap();
+        }
+        Command::Face(target, facing) => {
+            let target: Id = target.parse().map_err(|_| format!("there is no Id named {:?}", target))?;
+            let direction = match facing {
+                Facing::Up => Direction::from_deg(270f64),
+                Facing::Down => Direction::from_deg(90f64),
+                Facing::Left => Direction::from_deg(180f64),
+                Facing::Right => Direction::from_deg(0f64),
+            };
+            let entity = find(world, target)?;
+            let walk_cycles = world.read_storage::<WalkCycle>();
+            let animation = walk_cycles
+                .get(entity)
+                .and_then(|walk_cycle| walk_cycle.frames(direction))
+                .ok_or_else(|| format!("{:?} has no walk cycle", target))?;
+            if let Some(sprite_frame) = world.write_storage::<SpriteFrame>().get_mut(entity) {
+                sprite_frame.0 = animation.idle as f32;
+            }
+        }
+        Command::Bark(target, text) => {
+            let target: Id = target.parse().map_err(|_| format!("there is no Id named {:?}", target))?;
+            let text = PrettyString::parse(text).map_err(|error| error.to_string())?;
+            let entity = find(world, target)?;
+            world.write_storage::<SpeechBubble>().insert(entity, SpeechBubble::new(text)).unwrap();
+        }
+        Command::Timeout(frames, default) => {
+            let choices = world.read_resource::<DialogMessages>()
+                .current()
+                .and_then(|paragraph| paragraph.choices().as_ref().map(|choices| choices.len()))
+                .ok_or("the line has no choices to time")?;
+            if let Some(default) = default {
+                if *default > choices {
+                    return Err(format!("there are only {} choices", choices));
+                }
+            }
+            world.write_resource::<DialogTimeout>().start(*frames, *default);
+        }
+    }
+    Ok(())
+}
+
+/// The entity with the Id.
+fn find(world: &World, target: Id) -> Result<Entity, String> {
+    let entities = world.entities();
+    let id = world.read_storage::<Id>();
+    let found = (&*entities, &id)
+        .join()
+        .find(|(_, id)| **id == target)
+        .map(|(entity, _)| entity)
+        .ok_or_else(|| format!("there is no {:?} here", target));
+    found
+}
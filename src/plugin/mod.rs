@@ -1,12 +1,20 @@
 use game_engine::prelude::*;
 
 mod control;
+mod controls_menu;
 mod cutscene;
 mod debug;
 mod dialog;
+mod dialog_command;
 
 pub fn register<'a, 'b>(game: Game<'a, 'b>) -> Game<'a, 'b> {
-    game.plugin(control::process_control_events)
+    let game = game
+        .plugin(control::process_control_events)
+        .plugin(controls_menu::run_controls_menu)
         .plugin(dialog::manage_dialog)
-        .plugin(cutscene::process_cutscene)
+        .plugin(dialog_command::run_dialog_commands)
+        .plugin(cutscene::process_cutscene);
+    #[cfg(feature = "hot-reload")]
+    let game = game.pipe(crate::hot_reload::register);
+    game
 }
@@ -12,6 +12,9 @@ pub enum ControlEvent {
     Cancel(Option<Point>),
     Menu(Option<Point>),
     Run(Option<Point>),
+    Backlog(Option<Point>),
+    Auto(Option<Point>),
+    FastForward(Option<Point>),
 }
 
 #[derive(Clone, Default, Debug)]
@@ -39,4 +42,7 @@ pub struct ControlState {
     pub cancel: bool,
     pub menu: bool,
     pub run: bool,
+    pub backlog: bool,
+    pub auto: bool,
+    pub fast_forward: bool,
 }
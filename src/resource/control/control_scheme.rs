@@ -1,9 +1,47 @@
-//! Defines a mapping of inputs to controls
+//! Defines a mapping of inputs to controls, which is saved in a .toml file in the player's config
+//! directory so that the player can change it.
 
+use std::{
+    env,
+    fs,
+    io,
+    path::PathBuf,
+};
+use serde::{Serializer, Deserializer};
+use serde_derive::{Serialize, Deserialize};
 use game_engine::prelude::*;
 
-// TODO: these need to be Serialize/Deserialize so that the controls can be saved in a .toml
-//       this will require implementing Serialize/Deserialize for Key
+use super::key_names::{KEYS, key_name};
+
+/// The directory within the player's config directory that the game keeps its files in.
+const CONFIG_DIR: &str = "cat-game";
+/// The file the controls are saved in, within the `CONFIG_DIR`.
+const CONTROLS_FILE: &str = "controls.toml";
+
+const MOUSE_BUTTON_NAMES: &[(&str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+/// The names of the controls, in the order that `ControlScheme::controls` lists them.
+pub const CONTROL_NAMES: [&str; 11] = [
+    "Left",
+    "Right",
+    "Up",
+    "Down",
+    "Action",
+    "Cancel",
+    "Menu",
+    "Run",
+    "Backlog",
+    "Auto",
+    "Fast forward",
+];
+
+/// How many of the controls, from the start of `CONTROL_NAMES`, are directions. These are held
+/// down rather than pressed, so they only work as keys.
+pub const DIRECTIONS: usize = 4;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Control {
@@ -11,7 +49,53 @@ pub enum Control {
     MouseButton(MouseButton),
 }
 
-#[derive(Copy, Clone, Debug)]
+impl Control {
+    /// The name that the control is saved under, if it can be saved.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Control::Key(key) => KEYS
+                .iter()
+                .find(|named| *named == key)
+                .map(|key| key_name(*key)),
+            Control::MouseButton(button) => MOUSE_BUTTON_NAMES
+                .iter()
+                .find(|(_, named)| named == button)
+                .map(|(name, _)| String::from(*name)),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        KEYS
+            .iter()
+            .find(|key| key_name(**key) == name)
+            .map(|key| Control::Key(*key))
+            .or_else(|| MOUSE_BUTTON_NAMES
+                .iter()
+                .find(|(named, _)| *named == name)
+                .map(|(_, button)| Control::MouseButton(*button)))
+    }
+}
+
+impl serde::Serialize for Control {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.name() {
+            Some(name) => serializer.serialize_str(&name),
+            None => Err(serde::ser::Error::custom(format!("{:?} has no name to be saved under", self))),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Control {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Control::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("there is no key or mouse button named {:?}", name)))
+    }
+}
+
+/// The controls that are missing from the file are left as they are by default.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct ControlScheme {
     pub dir_left: Control,
     pub dir_right: Control,
@@ -21,6 +105,9 @@ pub struct ControlScheme {
     pub cancel: Control,
     pub menu: Control,
     pub run: Control,
+    pub backlog: Control,
+    pub auto: Control,
+    pub fast_forward: Control,
 }
 
 impl Default for ControlScheme {
@@ -34,6 +121,104 @@ impl Default for ControlScheme {
             cancel: Control::Key(Key::C),
             menu: Control::Key(Key::X),
             run: Control::Key(Key::LShift),
+            backlog: Control::Key(Key::B),
+            auto: Control::Key(Key::Q),
+            fast_forward: Control::Key(Key::Tab),
+        }
+    }
+}
+
+impl ControlScheme {
+    /// The controls file, in the player's config directory: `$XDG_CONFIG_HOME` or `~/.config`, or
+    /// `%APPDATA%` on Windows.
+    fn path() -> io::Result<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .or_else(|| env::var_os("APPDATA"))
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "there is no config directory to save the controls in"))?;
+        Ok(config_dir.join(CONFIG_DIR).join(CONTROLS_FILE))
+    }
+
+    /// Loads the saved controls, or the default ones if they have not been saved. Controls that
+    /// cannot be read are reported, and the defaults are used instead.
+    pub fn load() -> Self {
+        let contents = match Self::path().and_then(fs::read_to_string) {
+            Ok(contents) => contents,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                eprintln!("Could not read the controls file: {}", error);
+                return Self::default();
+            }
+        };
+        toml::from_str(&contents).unwrap_or_else(|error| {
+            eprintln!("Could not read the controls file: {}", error);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let contents = toml::to_string(self).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)
+    }
+
+    /// Every control, in the order of `CONTROL_NAMES`.
+    pub fn controls(&self) -> [Control; 11] {
+        [
+            self.dir_left,
+            self.dir_right,
+            self.dir_up,
+            self.dir_down,
+            self.action,
+            self.cancel,
+            self.menu,
+            self.run,
+            self.backlog,
+            self.auto,
+            self.fast_forward,
+        ]
+    }
+
+    fn controls_mut(&mut self) -> [&mut Control; 11] {
+        [
+            &mut self.dir_left,
+            &mut self.dir_right,
+            &mut self.dir_up,
+            &mut self.dir_down,
+            &mut self.action,
+            &mut self.cancel,
+            &mut self.menu,
+            &mut self.run,
+            &mut self.backlog,
+            &mut self.auto,
+            &mut self.fast_forward,
+        ]
+    }
+
+    /// The index of the control other than the one at the `index` that the input is bound to.
+    pub fn conflict(&self, index: usize, control: Control) -> Option<usize> {
+        self.controls()
+            .iter()
+            .enumerate()
+            .find(|&(other, bound)| other != index && *bound == control)
+            .map(|(other, _)| other)
+    }
+
+    /// Binds the input to the control at the index of `CONTROL_NAMES`. If another control was
+    /// already bound to the input, the two swap inputs, so that nothing is left unbound, and the
+    /// index of the other control is returned.
+    pub fn bind(&mut self, index: usize, control: Control) -> Option<usize> {
+        let previous = self.controls()[index];
+        let conflict = self.conflict(index, control);
+        let mut controls = self.controls_mut();
+        *controls[index] = control;
+        if let Some(other) = conflict {
+            *controls[other] = previous;
         }
+        conflict
     }
 }
@@ -0,0 +1,56 @@
+use super::control_scheme::CONTROL_NAMES;
+
+/// The menu for changing the controls: whether it is open, which control is selected, and whether
+/// it is waiting for the next key or button to bind to that control.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ControlsMenu {
+    open: bool,
+    selected: usize,
+    capturing: bool,
+    /// What happened last, like a control being swapped with another that used the same key.
+    message: Option<String>,
+}
+
+impl ControlsMenu {
+    pub fn toggle(&mut self) {
+        *self = ControlsMenu { open: !self.open, ..ControlsMenu::default() };
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn up(&mut self) {
+        self.selected = (self.selected + CONTROL_NAMES.len() - 1) % CONTROL_NAMES.len();
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % CONTROL_NAMES.len();
+    }
+
+    /// The index of the selected control in `CONTROL_NAMES`.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn start_capturing(&mut self) {
+        self.capturing = true;
+        self.message = None;
+    }
+
+    pub fn stop_capturing(&mut self) {
+        self.capturing = false;
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(String::as_str)
+    }
+}
@@ -0,0 +1,60 @@
+//! The keys that can be saved in the controls file. Only the keys that are listed here can be
+//! bound, as there would be no way to save any other key.
+//!
+//! Each key is saved under the engine's own name for it, as its `Debug` output gives it, rather
+//! than under a name kept here that could stop matching the engine's. The list is kept to the keys
+//! that are named the same way by every windowing library the engine might use.
+
+use game_engine::prelude::*;
+
+pub(super) const KEYS: &[Key] = &[
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Space,
+    Key::Return,
+    Key::Tab,
+    Key::LShift,
+    Key::RShift,
+    Key::LAlt,
+    Key::RAlt,
+    Key::Comma,
+    Key::Period,
+    Key::Slash,
+    Key::Semicolon,
+    Key::Minus,
+    Key::Equals,
+    Key::Backslash,
+];
+
+/// The name that the key is saved under.
+pub(super) fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
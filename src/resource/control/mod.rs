@@ -2,14 +2,18 @@ use game_engine::Game;
 
 mod control_events;
 mod control_scheme;
+mod controls_menu;
+mod key_names;
 
 pub use self::{
     control_events::*,
     control_scheme::*,
+    controls_menu::*,
 };
 
 pub(super) fn register<'a, 'b>(game: Game<'a, 'b>) -> Game<'a, 'b> {
     game.add_resource(ControlEvents::default())
         .add_resource(ControlState::default())
-        .add_resource(ControlScheme::default())
+        .add_resource(ControlScheme::load())
+        .add_resource(ControlsMenu::default())
 }
@@ -0,0 +1,39 @@
+/// Whether the backlog of past dialog is open, and how far it has been scrolled back, in entries
+/// from the most recent one.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct DialogBacklog {
+    open: bool,
+    scroll: usize,
+}
+
+impl DialogBacklog {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.scroll = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.scroll = 0;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn scroll_back(&mut self, len: usize) {
+        if self.scroll + 1 < len {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_forward(&mut self) {
+        if self.scroll > 0 {
+            self.scroll -= 1;
+        }
+    }
+
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+}
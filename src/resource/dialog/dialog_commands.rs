@@ -0,0 +1,15 @@
+use crate::model::command::Command;
+
+/// The commands from the tags of the lines that were just shown, which are waiting to be run.
+#[derive(Clone, Default, Debug)]
+pub struct DialogCommands(Vec<Command>);
+
+impl DialogCommands {
+    pub fn add(&mut self, command: Command) {
+        self.0.push(command);
+    }
+
+    pub fn take(&mut self) -> Vec<Command> {
+        std::mem::replace(&mut self.0, vec![])
+    }
+}
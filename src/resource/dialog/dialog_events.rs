@@ -1,16 +1,80 @@
+use std::collections::VecDeque;
+use crate::model::dialog_event::DialogEvent;
+
+/// How many events are kept waiting for a cutscene before the oldest are forgotten.
+const MAX_EVENTS: usize = 32;
+
+/// The events that the dialogs have emitted, which wait here until a cutscene takes them. They are
+/// kept across frames, so a cutscene that only starts waiting after an event was emitted still
+/// gets it. Only the events of the current story (by its number in `DialogMessages`) are kept, so
+/// an event left over from an earlier story cannot be mistaken for a new one.
 #[derive(Clone, Default, Debug)]
-pub struct DialogEvents(Vec<String>);
+pub struct DialogEvents {
+    story: usize,
+    events: VecDeque<DialogEvent>,
+}
 
 impl DialogEvents {
-    pub fn clear(&mut self) {
-        self.0.clear();
+    pub fn add(&mut self, story: usize, event: DialogEvent) {
+        self.follow(story);
+        if self.events.len() == MAX_EVENTS {
+            if let Some(event) = self.events.pop_front() {
+                eprintln!("Dialog event {:?} was never waited for", event);
+            }
+        }
+        self.events.push_back(event);
+    }
+
+    /// Takes the oldest event of the `story` that is equal to the one given, if it has been
+    /// emitted.
+    pub fn take(&mut self, story: usize, event: &DialogEvent) -> bool {
+        self.follow(story);
+        match self.events.iter().position(|emitted| emitted == event) {
+            Some(index) => {
+                self.events.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DialogEvent> {
+        self.events.iter()
+    }
+
+    /// Forgets the events of the previous story once another one has started.
+    fn follow(&mut self, story: usize) {
+        if story != self.story {
+            self.events.clear();
+            self.story = story;
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub fn add(&mut self, event: String) {
-        self.0.push(event);
+    #[test]
+    fn take() {
+        let mut events = DialogEvents::default();
+        events.add(1, DialogEvent::ComeOut);
+        assert!(events.take(1, &DialogEvent::ComeOut));
+        assert!(!events.take(1, &DialogEvent::ComeOut));
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &String> {
-        self.0.iter()
+    #[test]
+    fn stale_events() {
+        let mut events = DialogEvents::default();
+        events.add(1, DialogEvent::ComeOut);
+        assert!(!events.take(2, &DialogEvent::ComeOut));
+        events.add(2, DialogEvent::ComeOut);
+        assert!(events.take(2, &DialogEvent::ComeOut));
+        assert!(!events.take(2, &DialogEvent::ComeOut));
+
+        events.add(3, DialogEvent::ComeOut);
+        events.add(4, DialogEvent::ComeOut);
+        assert!(events.take(4, &DialogEvent::ComeOut));
+        assert_eq!(events.iter().count(), 0);
     }
 }
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use crate::model::message::Message;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum HistoryEntry {
+    Line(Message),
+    Choice(Message),
+}
+
+/// Every paragraph that has been shown and every choice that was made, grouped by conversation,
+/// so that they can be read again in the backlog. The conversations are shared with the
+/// `BacklogDrawable`, and only copied if they change while it is drawing them.
+#[derive(Clone, Default, Debug)]
+pub struct DialogHistory {
+    conversations: Arc<Vec<Vec<HistoryEntry>>>,
+    in_conversation: bool,
+    /// The number (from `DialogMessages::number`) of the last paragraph that was recorded.
+    recorded: Option<usize>,
+}
+
+impl DialogHistory {
+    /// Records the paragraph that is being shown, unless it has been recorded already.
+    pub fn show(&mut self, number: usize, text: &str) {
+        if self.recorded == Some(number) {
+            return;
+        }
+        let conversations = Arc::make_mut(&mut self.conversations);
+        if !self.in_conversation {
+            conversations.push(vec![]);
+            self.in_conversation = true;
+        }
+        conversations.last_mut().unwrap().push(HistoryEntry::Line(Message::from(text)));
+        self.recorded = Some(number);
+    }
+
+    pub fn choose(&mut self, text: &str) {
+        if let Some(conversation) = Arc::make_mut(&mut self.conversations).last_mut() {
+            conversation.push(HistoryEntry::Choice(Message::from(text)));
+        }
+    }
+
+    /// The dialog has closed, so the next paragraph starts a new conversation.
+    pub fn end(&mut self) {
+        self.in_conversation = false;
+    }
+
+    pub fn conversations(&self) -> Arc<Vec<Vec<HistoryEntry>>> {
+        self.conversations.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.conversations.iter().map(Vec::len).sum()
+    }
+}
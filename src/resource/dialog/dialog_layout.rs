@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+use game_engine::prelude::*;
+
+#[derive(Clone, Default, Debug)]
+struct Layout {
+    dialog_box: Option<Rect>,
+    choices: Vec<Rect>,
+    pages: Option<(String, Vec<usize>)>,
+}
+
+/// Where the dialog was last drawn, so that the mouse can interact with it. The layout is only
+/// known while rendering, so the `DialogDrawable` shares this with the resource and fills it in.
+#[derive(Clone, Default, Debug)]
+pub struct DialogLayout(Arc<Mutex<Layout>>);
+
+fn contains(rect: &Rect, point: Point) -> bool {
+    rect.overlaps(&Rect::new(point.x, point.y, 1, 1))
+}
+
+impl DialogLayout {
+    pub fn clear(&self) {
+        let mut layout = self.0.lock().unwrap();
+        layout.dialog_box = None;
+        layout.choices.clear();
+    }
+
+    pub fn set_dialog_box(&self, dialog_box: Rect) {
+        self.0.lock().unwrap().dialog_box = Some(dialog_box);
+    }
+
+    pub fn set_choices(&self, choices: Vec<Rect>) {
+        self.0.lock().unwrap().choices = choices;
+    }
+
+    /// Records how the text of a paragraph was split into pages, by the length of each page.
+    pub fn set_pages(&self, text: &str, pages: Vec<usize>) {
+        self.0.lock().unwrap().pages = Some((text.to_owned(), pages));
+    }
+
+    /// The length of each page of a paragraph, if it has been laid out yet.
+    pub fn pages(&self, text: &str) -> Option<Vec<usize>> {
+        match &self.0.lock().unwrap().pages {
+            Some((laid_out, pages)) if laid_out == text => Some(pages.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn dialog_box_contains(&self, point: Point) -> bool {
+        self.0.lock().unwrap().dialog_box.map(|dialog_box| contains(&dialog_box, point)).unwrap_or(false)
+    }
+
+    /// The index of the choice under the point, if any.
+    pub fn choice_at(&self, point: Point) -> Option<usize> {
+        self.0.lock().unwrap().choices.iter().position(|choice| contains(choice, point))
+    }
+}
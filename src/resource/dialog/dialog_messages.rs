@@ -4,7 +4,12 @@ use inkgen::runtime::{Story, Paragraph};
 #[derive(Default, Debug)]
 pub struct DialogMessages {
     story: Mutex<Option<Story>>,
-    paragraph: Option<Paragraph>
+    paragraph: Option<Paragraph>,
+    /// How many paragraphs have been shown, which tells one paragraph from the next even when
+    /// their text is the same.
+    number: usize,
+    /// How many stories have been started, which tells one story from the next.
+    stories: usize,
 }
 
 impl DialogMessages {
@@ -14,9 +19,21 @@ impl DialogMessages {
             _ => panic!("The story to start must not be started"),
         };
         self.paragraph = Some(paragraph);
+        self.number += 1;
+        self.stories += 1;
         *self.story.lock().unwrap() = Some(story);
     }
 
+    /// The number of the current paragraph, which changes whenever the paragraph does.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// The number of the current story, which changes whenever a story is started.
+    pub fn story(&self) -> usize {
+        self.stories
+    }
+
     pub fn current(&self) -> Option<&Paragraph> {
         self.paragraph.as_ref()
     }
@@ -37,6 +54,7 @@ impl DialogMessages {
             Some(Story::Regular(regular_story)) => {
                 let (paragraph, next_story) = regular_story.next();
                 self.paragraph = Some(paragraph);
+                self.number += 1;
                 *story = Some(next_story);
             }
         }
@@ -55,6 +73,7 @@ impl DialogMessages {
             Some(Story::Regular(regular_story)) => {
                 let (paragraph, next_story) = regular_story.select(option);
                 self.paragraph = Some(paragraph);
+                self.number += 1;
                 *story = Some(next_story);
             }
         }
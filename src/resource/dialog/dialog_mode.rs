@@ -0,0 +1,53 @@
+/// How many frames the auto mode waits after a page is shown, before it moves on.
+const AUTO_DELAY_FRAMES: u32 = 60;
+/// How many more frames the auto mode waits for each glyph on the page, to give time to read it.
+const AUTO_FRAMES_PER_GLYPH: u32 = 3;
+
+/// The modes that move the dialog along without the player pressing anything. In auto mode, each
+/// page is moved past after long enough to read it. While fast forwarding, the lines that have
+/// been seen before are rushed through. Both of them stop at choices.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct DialogMode {
+    auto: bool,
+    fast_forward: bool,
+    /// How many frames the current page has been waiting to be moved past.
+    waited: u32,
+}
+
+impl DialogMode {
+    pub fn toggle_auto(&mut self) {
+        self.auto = !self.auto;
+        self.waited = 0;
+    }
+
+    pub fn is_auto(&self) -> bool {
+        self.auto
+    }
+
+    pub fn set_fast_forward(&mut self, fast_forward: bool) {
+        self.fast_forward = fast_forward;
+    }
+
+    pub fn is_fast_forwarding(&self) -> bool {
+        self.fast_forward
+    }
+
+    /// Counts another frame of waiting on a page of `len` glyphs, and returns whether the auto
+    /// mode has waited long enough to move on.
+    pub fn wait(&mut self, len: usize) -> bool {
+        if !self.auto {
+            return false;
+        }
+        self.waited += 1;
+        if self.waited >= AUTO_DELAY_FRAMES + AUTO_FRAMES_PER_GLYPH * len as u32 {
+            self.waited = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn stop_waiting(&mut self) {
+        self.waited = 0;
+    }
+}
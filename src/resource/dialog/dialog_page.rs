@@ -0,0 +1,18 @@
+/// Which page of the current paragraph is shown, when it is too long to fit in the dialog box all
+/// at once.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct DialogPage(usize);
+
+impl DialogPage {
+    pub fn current(&self) -> usize {
+        self.0
+    }
+
+    pub fn next(&mut self) {
+        self.0 += 1;
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
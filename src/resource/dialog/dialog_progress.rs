@@ -1,32 +1,57 @@
 #[derive(Clone, PartialEq, Debug)]
-pub struct DialogProgress(Option<f32>);
+pub struct DialogProgress {
+    index: Option<f32>,
+    /// The frames left in the pause that the typewriter is holding at.
+    hold: u32,
+    /// Whether the typewriter has moved since it was reset. Until it has, the pauses at the very
+    /// start of the text have not been reached, even though the first glyph is already shown.
+    moved: bool,
+}
 
 impl Default for DialogProgress {
     fn default() -> Self {
-        DialogProgress(Some(1f32))
+        DialogProgress { index: Some(1f32), hold: 0, moved: false }
     }
 }
 
 impl DialogProgress {
     pub fn reset(&mut self) {
-        self.0 = Some(1f32);
+        self.index = Some(1f32);
+        self.hold = 0;
+        self.moved = false;
     }
 
     pub fn skip(&mut self) {
-        self.0 = None;
+        self.index = None;
+        self.hold = 0;
     }
 
-    pub fn progress(&mut self, amt: f32, limit: usize) {
-        if let Some(prev) = self.0 {
-            if (prev + amt) as usize >= limit {
-                self.0 = None;
+    /// Moves the typewriter along, stopping at each of the `pauses` (the number of characters
+    /// before the pause, and the number of frames it lasts) that it reaches.
+    pub fn progress(&mut self, amt: f32, limit: usize, pauses: &[(usize, u32)]) {
+        if let Some(prev) = self.index {
+            if self.hold > 0 {
+                self.hold -= 1;
+                return;
+            }
+            let next = prev + amt;
+            let moved = self.moved;
+            self.moved = true;
+            let pause = pauses
+                .iter()
+                .find(|(position, _)| (!moved || (prev as usize) < *position) && *position <= next as usize);
+            if let Some((position, frames)) = pause {
+                self.index = Some(*position as f32);
+                self.hold = *frames;
+            } else if next as usize >= limit {
+                self.index = None;
             } else {
-                self.0 = Some(prev + amt);
+                self.index = Some(next);
             }
         }
     }
 
     pub fn current(&self) -> Option<usize> {
-        self.0.map(|amt| amt as usize)
+        self.index.map(|amt| amt as usize)
     }
 }
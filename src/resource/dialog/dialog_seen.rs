@@ -0,0 +1,15 @@
+use std::collections::HashSet;
+
+/// The lines that have been read already, so that fast forwarding can rush through them.
+#[derive(Clone, Default, Debug)]
+pub struct DialogSeen(HashSet<String>);
+
+impl DialogSeen {
+    pub fn mark(&mut self, text: String) {
+        self.0.insert(text);
+    }
+
+    pub fn has_seen(&self, text: &str) -> bool {
+        self.0.contains(text)
+    }
+}
@@ -1,32 +1,54 @@
+/// Which of the choices is selected. Choices whose conditions are false are not shown, so the
+/// selection is among the ones that are, which are kept as their indices in the paragraph.
 #[derive(Clone, PartialEq, Default, Debug)]
 pub struct DialogSelection {
-    count: usize,
+    choices: Vec<usize>,
     current: usize,
 }
 
 impl DialogSelection {
-    pub fn set_up(&mut self, count: usize) {
-        self.count = count;
+    pub fn set_up(&mut self, choices: Vec<usize>) {
+        self.choices = choices;
         self.current = 0;
     }
 
+    /// The indices of the choices that are shown.
+    pub fn choices(&self) -> &[usize] {
+        &self.choices
+    }
+
     pub fn up(&mut self) {
-        if self.count != 0 {
-            self.current = (self.current + self.count - 1) % self.count;
+        let count = self.choices.len();
+        if count != 0 {
+            self.current = (self.current + count - 1) % count;
         } else {
             self.current = 0;
         }
     }
 
     pub fn down(&mut self) {
-        if self.count != 0 {
-            self.current = (self.current + 1) % self.count;
+        let count = self.choices.len();
+        if count != 0 {
+            self.current = (self.current + 1) % count;
         } else {
             self.current = 0;
         }
     }
 
+    /// Selects the choice at the index among the ones that are shown.
+    pub fn select(&mut self, index: usize) {
+        if index < self.choices.len() {
+            self.current = index;
+        }
+    }
+
+    /// The index of the selected choice among the ones that are shown.
+    pub fn selected(&self) -> usize {
+        self.current
+    }
+
+    /// The selected choice, numbered from 1 among all of the paragraph's choices.
     pub fn current(&self) -> usize {
-        self.current + 1
+        self.choices.get(self.current).cloned().unwrap_or(0) + 1
     }
 }
@@ -0,0 +1,49 @@
+/// A deadline on the current choice, set by a `#timeout:frames` tag on the line the choices are
+/// part of. It only counts down while the choices are shown, and when it runs out a choice is
+/// picked for the player.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct DialogTimeout {
+    frames: u32,
+    left: u32,
+    /// The choice that is picked when time runs out, numbered from 1 among all of the paragraph's
+    /// choices, like `DialogSelection::current`.
+    default: Option<usize>,
+}
+
+impl DialogTimeout {
+    pub fn start(&mut self, frames: u32, default: Option<usize>) {
+        self.frames = frames;
+        self.left = frames;
+        self.default = default;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Counts down a frame, and returns whether time has just run out.
+    pub fn tick(&mut self) -> bool {
+        if self.left == 0 {
+            return false;
+        }
+        self.left -= 1;
+        self.left == 0
+    }
+
+    /// How much of the time is left, from 1 down to 0, if there is a deadline.
+    pub fn remaining(&self) -> Option<f32> {
+        if self.frames == 0 {
+            None
+        } else {
+            Some(self.left as f32 / self.frames as f32)
+        }
+    }
+
+    /// The index of the choice to pick among the `choices` that are shown. This is the default
+    /// choice if it is shown, or else the last one, which is where a "silence" option belongs.
+    pub fn pick(&self, choices: &[usize]) -> usize {
+        self.default
+            .and_then(|default| choices.iter().position(|&index| index + 1 == default))
+            .unwrap_or_else(|| choices.len().saturating_sub(1))
+    }
+}
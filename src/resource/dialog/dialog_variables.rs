@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+use crate::model::condition::Value;
+
+/// The variables that the dialogs set with `#set:name=value` tags. They last between dialogs, and
+/// the rest of the game can read and set them too.
+#[derive(Clone, Default, Debug)]
+pub struct DialogVariables(HashMap<String, Value>);
+
+impl DialogVariables {
+    /// The value of the variable, which is `false` if it has never been set.
+    pub fn get(&self, name: &str) -> Value {
+        self.0.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: Value) {
+        self.0.insert(name.into(), value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+}
@@ -1,23 +1,50 @@
 use game_engine::Game;
 
+mod dialog_backlog;
+mod dialog_commands;
 mod dialog_events;
+mod dialog_history;
+mod dialog_layout;
 mod dialog_messages;
+mod dialog_mode;
+mod dialog_page;
 mod dialog_selection;
 mod dialog_progress;
+mod dialog_seen;
 mod dialog_speed;
+mod dialog_timeout;
+mod dialog_variables;
 
 pub use self::{
+    dialog_backlog::*,
+    dialog_commands::*,
     dialog_events::*,
+    dialog_history::*,
+    dialog_layout::*,
     dialog_messages::*,
+    dialog_mode::*,
+    dialog_page::*,
     dialog_selection::*,
     dialog_progress::*,
+    dialog_seen::*,
     dialog_speed::*,
+    dialog_timeout::*,
+    dialog_variables::*,
 };
 
 pub fn register(game: Game<'a, 'b>) -> Game<'a, 'b> {
     game.add_resource(DialogEvents::default())
         .add_resource(DialogSpeed::default())
         .add_resource(DialogMessages::default())
+        .add_resource(DialogLayout::default())
+        .add_resource(DialogHistory::default())
+        .add_resource(DialogBacklog::default())
         .add_resource(DialogSelection::default())
         .add_resource(DialogProgress::default())
+        .add_resource(DialogPage::default())
+        .add_resource(DialogVariables::default())
+        .add_resource(DialogCommands::default())
+        .add_resource(DialogMode::default())
+        .add_resource(DialogSeen::default())
+        .add_resource(DialogTimeout::default())
 }
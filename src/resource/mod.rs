@@ -6,11 +6,15 @@ pub mod cutscene;
 pub mod dialog;
 pub mod door_transition;
 pub mod state;
+pub mod tile_animations;
+pub mod tile_properties;
 
 pub fn register(game: Game<'a, 'b>) -> Game<'a, 'b> {
     game.add_resource(constant::BaseMovementSpeed::default())
         .add_resource(door_transition::DoorTransition::new("shop"))
         .add_resource(state::State::default())
+        .add_resource(tile_animations::TileAnimations::default())
+        .add_resource(tile_properties::TilePropertyLayers::default())
         .pipe(control::register)
         .pipe(dialog::register)
         .pipe(cutscene::register)
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum MainState {
     Start,
@@ -12,3 +14,19 @@ impl Default for MainState {
         MainState::Start
     }
 }
+
+/// Parses the name of a state, as the dialogs refer to them.
+impl FromStr for MainState {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "Start" => Ok(MainState::Start),
+            "RunToTheAlley" => Ok(MainState::RunToTheAlley),
+            "ArriveInTheAlley" => Ok(MainState::ArriveInTheAlley),
+            "HideSomewhere" => Ok(MainState::HideSomewhere),
+            "End" => Ok(MainState::End),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+use game_engine::prelude::*;
+use crate::model::tile_animation::TileAnimation;
+
+/// A layer of the `TileLayers` that has animated tiles, along with the tiles it was made from, so
+/// that it can be made again with the frames that its animations are showing.
+struct AnimatedLayer {
+    depth: i32,
+    size: Dimen,
+    tiles: &'static [Option<Tile>],
+    /// The index of each animated tile in the grid, along with its animation.
+    animations: &'static [(usize, &'static TileAnimation)],
+    /// The frame that each of the animations was last shown at.
+    frames: Vec<usize>,
+}
+
+/// The animated tiles of each of the `TileLayers`, by depth.
+#[derive(Default)]
+pub struct TileAnimations {
+    layers: Vec<AnimatedLayer>,
+    elapsed: Duration,
+}
+
+impl TileAnimations {
+    pub fn clear(&mut self) {
+        self.layers.clear();
+        self.elapsed = Duration::default();
+    }
+
+    /// Animates the tiles of the layer at `depth`, which is a grid of the `size` made from the
+    /// `tiles`.
+    pub fn set(
+        &mut self,
+        depth: i32,
+        size: Dimen,
+        tiles: &'static [Option<Tile>],
+        animations: &'static [(usize, &'static TileAnimation)],
+    ) {
+        self.layers.retain(|layer| layer.depth != depth);
+        if !animations.is_empty() {
+            self.layers.push(AnimatedLayer {
+                depth,
+                size,
+                tiles,
+                animations,
+                // the layer was made from the tiles as they are, which may not be the first frame
+                frames: vec![::std::usize::MAX; animations.len()],
+            });
+        }
+    }
+
+    /// Runs the animations for the time since the last frame, and returns the layers that have
+    /// changed since then, to be put back in the `TileLayers`.
+    pub fn advance(&mut self, delta: Duration) -> Vec<(i32, TileGrid)> {
+        self.elapsed += delta;
+        let millis = self.elapsed.as_secs() * 1000 + self.elapsed.subsec_millis() as u64;
+        let mut changed = vec![];
+        for layer in &mut self.layers {
+            let mut is_changed = false;
+            for ((_, animation), frame) in layer.animations.iter().zip(layer.frames.iter_mut()) {
+                let index = animation.frame_at(millis);
+                is_changed |= index != *frame;
+                *frame = index;
+            }
+            if !is_changed {
+                continue;
+            }
+            let mut tiles = layer.tiles.to_vec();
+            for ((index, _), frame) in layer.animations.iter().zip(&layer.frames) {
+                if let Some(Some(tile)) = tiles.get_mut(*index) {
+                    tile.index = *frame;
+                }
+            }
+            changed.push((layer.depth, TileGrid::new(Point::new(0, 0), layer.size, tiles)));
+        }
+        changed
+    }
+}
@@ -0,0 +1,32 @@
+use game_engine::prelude::*;
+use crate::model::tile_properties::TilePropertyLayer;
+
+/// The tiles with custom properties in each of the `TileLayers`, by depth, so that systems can ask
+/// what is under a point (e.g. what surface the player is walking on).
+#[derive(Clone, Default, Debug)]
+pub struct TilePropertyLayers {
+    layers: Vec<(i32, &'static TilePropertyLayer)>,
+}
+
+impl TilePropertyLayers {
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    pub fn set(&mut self, depth: i32, layer: &'static TilePropertyLayer) {
+        self.layers.retain(|(layer, _)| *layer != depth);
+        if !layer.is_empty() {
+            self.layers.push((depth, layer));
+            self.layers.sort_by_key(|(depth, _)| -depth);
+        }
+    }
+
+    /// The value of a property of the topmost tile under a point which has that property.
+    pub fn find(&self, point: Point<f32>, name: &str) -> Option<&'static str> {
+        self.layers
+            .iter()
+            .filter_map(|(_, layer)| layer.at(point))
+            .filter_map(|properties| properties.get(name))
+            .next()
+    }
+}
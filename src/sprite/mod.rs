@@ -1,5 +1,6 @@
 use game_engine::prelude::*;
 use crate::image;
+use crate::model::sprite_animation::SpriteAnimation;
 
 include!(concat!(env!("OUT_DIR"), "/sprites.rs"));
 
@@ -1,2 +1,6 @@
+mod tiles;
 mod walk_cycle;
-pub use self::walk_cycle::AnimateWalkCycle;
+pub use self::{
+    tiles::AnimateTiles,
+    walk_cycle::AnimateWalkCycle,
+};
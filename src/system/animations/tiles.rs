@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+use game_engine::{system, prelude::*};
+use crate::resource::tile_animations::TileAnimations;
+
+/// The longest time that is counted for one frame, so that the animations do not skip ahead after
+/// the game has been held up (e.g. while the window is being dragged).
+const MAX_DELTA: Duration = Duration::from_millis(100);
+
+/// Animates the animated tiles of the `TileLayers`, by switching each of them to the frame its
+/// animation should be showing.
+#[derive(Default, Debug)]
+pub struct AnimateTiles {
+    last_frame: Option<Instant>,
+}
+
+system! {
+    impl AnimateTiles {
+        fn run(
+            &mut self,
+            tile_animations: &mut Resource<TileAnimations>,
+            tile_layers: &mut Resource<TileLayers>,
+        ) {
+            let now = Instant::now();
+            let delta = self.last_frame
+                .map(|last_frame| Duration::min(now - last_frame, MAX_DELTA))
+                .unwrap_or_default();
+            self.last_frame = Some(now);
+            for (depth, grid) in tile_animations.advance(delta) {
+                tile_layers.set(depth, grid);
+            }
+        }
+    }
+}
@@ -6,8 +6,8 @@ use crate::component::{
 };
 
 /// Animates a character's walk cycle. The speed is reduced by half when the character is not
-/// moving but is trying to (such as when colliding with a wall). It is assumed that the
-/// first frame of each direction is the idle frame, and then rest are used for the cycle.
+/// moving but is trying to (such as when colliding with a wall). The animation for each direction
+/// shows its idle frame when the character stops.
 ///
 /// TODO: might be nice to have the animation run faster when the character is running (moving at
 /// double the BaseMovementSpeed) or even have a speed proportional to Velocity/BaseMovementSpeed,
@@ -43,19 +43,18 @@ system! {
                     };
 
                 if velocity.magnitude() == 0f32 || animation_speed.0 == 0f32 {
-                    let frames = walk_cycle.directions
+                    let animation = walk_cycle.directions
                         .iter()
-                        .map(|(_, range)| range)
-                        .find(|range| range.contains(&sprite_frame.current()));
-                    if let Some(frames) = frames {
-                        sprite_frame.0 = frames.start as f32;
+                        .map(|(_, animation)| animation)
+                        .find(|animation| animation.contains(sprite_frame.current()));
+                    if let Some(animation) = animation {
+                        sprite_frame.0 = animation.idle as f32;
                     }
                 } else {
-                    if let Some(frames) = walk_cycle.frames(velocity.direction().unwrap()) {
+                    if let Some(animation) = walk_cycle.frames(velocity.direction().unwrap()) {
                         sprite_frame.0 += image_speed;
-                        let walking_frames = frames.start + 1..frames.end;
-                        if !walking_frames.contains(&sprite_frame.current()) {
-                            sprite_frame.0 = walking_frames.start as f32;
+                        if !animation.frames.contains(&sprite_frame.current()) {
+                            sprite_frame.0 = animation.frames.start as f32;
                         }
                     }
                 }
@@ -1,4 +1,5 @@
 pub mod apply_velocity;
 pub mod camera_target;
 pub mod loader;
+pub mod speech_bubbles;
 pub mod state_pickups;
@@ -0,0 +1,25 @@
+use game_engine::{system, prelude::*};
+
+use crate::component::speech_bubble::SpeechBubble;
+
+/// Takes down the speech bubbles that have been up long enough.
+#[derive(Default, Debug)]
+pub struct ExpireSpeechBubbles;
+
+system! {
+    impl ExpireSpeechBubbles {
+        fn run(
+            &mut self,
+            entities: &Entities,
+            speech_bubble: &mut Component<SpeechBubble>,
+        ) {
+            let expired: Vec<Entity> = (&*entities, &mut speech_bubble)
+                .join()
+                .filter_map(|(entity, speech_bubble)| if speech_bubble.tick() { None } else { Some(entity) })
+                .collect();
+            for entity in expired {
+                speech_bubble.remove(entity);
+            }
+        }
+    }
+}
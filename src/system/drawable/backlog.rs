@@ -0,0 +1,31 @@
+use game_engine::{system, prelude::*};
+use crate::drawable::BacklogDrawable;
+use crate::resource::dialog::{DialogHistory, DialogBacklog};
+
+#[derive(Default, Debug)]
+pub struct MaintainBacklogDrawable;
+
+system! {
+    impl MaintainBacklogDrawable {
+        fn run(
+            &mut self,
+            drawable: &mut Component<Box<dyn Drawable>>,
+            dialog_history: &Resource<DialogHistory>,
+            dialog_backlog: &Resource<DialogBacklog>,
+        ) {
+            for drawable in (&mut drawable).join() {
+                if let Some(drawable) = drawable.as_any_mut().downcast_mut::<BacklogDrawable>() {
+                    drawable.open = dialog_backlog.is_open();
+                    drawable.scroll = dialog_backlog.scroll();
+                    // the history is only shared while the backlog is open, so that it is not
+                    // copied when it changes while the backlog is closed
+                    if drawable.open {
+                        drawable.conversations = dialog_history.conversations();
+                    } else if !drawable.conversations.is_empty() {
+                        drawable.conversations = Default::default();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use game_engine::{system, prelude::*};
+use crate::drawable::ControlsDrawable;
+use crate::resource::control::{ControlScheme, ControlsMenu, CONTROL_NAMES};
+
+#[derive(Default, Debug)]
+pub struct MaintainControlsDrawable;
+
+system! {
+    impl MaintainControlsDrawable {
+        fn run(
+            &mut self,
+            drawable: &mut Component<Box<dyn Drawable>>,
+            control_scheme: &Resource<ControlScheme>,
+            controls_menu: &Resource<ControlsMenu>,
+        ) {
+            for drawable in (&mut drawable).join() {
+                if let Some(drawable) = drawable.as_any_mut().downcast_mut::<ControlsDrawable>() {
+                    drawable.open = controls_menu.is_open();
+                    if drawable.open {
+                        drawable.controls = CONTROL_NAMES
+                            .iter()
+                            .cloned()
+                            .zip(control_scheme.controls().iter())
+                            .map(|(name, control)| (name, control.name().map(String::from).unwrap_or_else(|| format!("{:?}", control))))
+                            .collect();
+                        drawable.selected = controls_menu.selected();
+                        drawable.capturing = controls_menu.is_capturing();
+                        drawable.message = controls_menu.message().map(String::from);
+                    } else {
+                        drawable.controls.clear();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use game_engine::{system, prelude::*};
 use crate::drawable::DialogDrawable;
-use crate::resource::dialog::{DialogMessages, DialogProgress, DialogSelection};
+use crate::model::message::Message;
+use crate::resource::dialog::{DialogMessages, DialogProgress, DialogSelection, DialogLayout, DialogPage, DialogMode, DialogTimeout};
 
 #[derive(Default, Debug)]
 pub struct MaintainDialogDrawable;
@@ -13,12 +14,40 @@ system! {
             dialog_messages: &Resource<DialogMessages>,
             dialog_progress: &Resource<DialogProgress>,
             dialog_selection: &Resource<DialogSelection>,
+            dialog_layout: &Resource<DialogLayout>,
+            dialog_page: &Resource<DialogPage>,
+            dialog_mode: &Resource<DialogMode>,
+            dialog_timeout: &Resource<DialogTimeout>,
         ) {
             for drawable in (&mut drawable).join() {
                 if let Some(drawable) = drawable.as_any_mut().downcast_mut::<DialogDrawable>() {
+                    let paragraph = dialog_messages.current().cloned();
+                    if drawable.paragraph != paragraph {
+                        drawable.clear_revealed();
+                    }
+                    drawable.frame = drawable.frame.wrapping_add(1);
+                    drawable.auto = dialog_mode.is_auto();
+                    drawable.fast_forward = dialog_mode.is_fast_forwarding();
+                    drawable.timeout = dialog_timeout.remaining();
                     drawable.index = dialog_progress.current();
-                    drawable.paragraph = dialog_messages.current().cloned();
-                    drawable.selection = dialog_selection.current() - 1;
+                    drawable.page = dialog_page.current();
+                    if let Some(paragraph) = &paragraph {
+                        // the typewriter counts from the start of the page
+                        let pages = dialog_layout.pages(&paragraph.text()).unwrap_or_default();
+                        let start: usize = pages.iter().take(drawable.page).sum();
+                        let shown = match dialog_progress.current() {
+                            Some(index) => start + index,
+                            None => pages
+                                .get(drawable.page)
+                                .map(|len| start + len)
+                                .unwrap_or_else(|| Message::from(paragraph.text()).len()),
+                        };
+                        drawable.reveal(shown);
+                    }
+                    drawable.paragraph = paragraph;
+                    drawable.choices = dialog_selection.choices().to_vec();
+                    drawable.selection = dialog_selection.selected();
+                    drawable.layout = dialog_layout.clone();
                 }
             }
         }
@@ -1,3 +1,6 @@
+pub mod backlog;
+pub mod controls;
 pub mod dialog;
 pub mod loading;
+pub mod speech_bubble;
 pub mod sprite;
@@ -0,0 +1,40 @@
+use game_engine::{system, prelude::*};
+use crate::drawable::SpeechBubbleDrawable;
+use crate::component::{
+    marker,
+    position::Position,
+    speech_bubble::SpeechBubble,
+};
+
+#[derive(Default, Debug)]
+pub struct MaintainSpeechBubbleDrawable;
+
+system! {
+    impl MaintainSpeechBubbleDrawable {
+        fn run(
+            &mut self,
+            drawable: &mut Component<Box<dyn Drawable>>,
+            position: &Component<Position>,
+            speech_bubble: &Component<SpeechBubble>,
+            camera_target: &Component<marker::CameraTarget>,
+            current_scene: &Resource<CurrentScene>,
+        ) {
+            let focus = (&position, &camera_target).join().next().map(|(position, _)| position.rounded());
+            let mut bubbles: Vec<_> = (&position, &speech_bubble)
+                .join()
+                .map(|(position, speech_bubble)| (position.rounded(), speech_bubble.text.clone()))
+                .collect();
+            // the speakers lower on the screen are in front, so their bubbles are too
+            bubbles.sort_by_key(|(position, _)| position.y);
+            for drawable in (&mut drawable).join() {
+                if let Some(drawable) = drawable.as_any_mut().downcast_mut::<SpeechBubbleDrawable>() {
+                    if let Some(focus) = focus {
+                        drawable.focus = focus;
+                    }
+                    drawable.bounds = current_scene.current().bounds();
+                    drawable.bubbles = bubbles.clone();
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,15 @@
 use game_engine::{system, prelude::*};
 use crate::component::{
     marker::Player,
+    position::Position,
     velocity::Velocity,
 };
 use crate::resource::{
     constant::BaseMovementSpeed,
     control::ControlState,
-    dialog::DialogMessages,
+    dialog::{DialogMessages, DialogBacklog},
     cutscene::CurrentCutscene,
+    tile_properties::TilePropertyLayers,
 };
 
 #[derive(Default, Debug)]
@@ -19,10 +21,13 @@ system! {
             &mut self,
             velocity: &mut Component<Velocity>,
             player: &Component<Player>,
+            position: &Component<Position>,
             control_state: &Resource<ControlState>,
             base_movement_speed: &Resource<BaseMovementSpeed>,
             current_cutscene: &Resource<CurrentCutscene>,
             dialog_messages: &Resource<DialogMessages>,
+            dialog_backlog: &Resource<DialogBacklog>,
+            tile_property_layers: &Resource<TilePropertyLayers>,
         ) {
             let axis_h = control_state.axis_h as f32;
             let axis_v = control_state.axis_v as f32;
@@ -32,12 +37,17 @@ system! {
             let hspeed = axis_h / scale * movement_speed;
             let vspeed = axis_v / scale * movement_speed;
 
-            for (_, velocity) in (&player, &mut velocity).join() {
-                if dialog_messages.current().is_some() || !current_cutscene.is_over() {
-                    // disable player control while dialog is visible
+            for (_, position, velocity) in (&player, &position, &mut velocity).join() {
+                if dialog_messages.current().is_some() || dialog_backlog.is_open() || !current_cutscene.is_over() {
+                    // disable player control while dialog or the backlog is visible
                     velocity.0 = Point::default();
                 } else {
-                    velocity.0 = Point::new(hspeed, vspeed);
+                    // terrain like mud or deep water slows the player down with its `speed` property
+                    let terrain_speed = tile_property_layers
+                        .find(position.0, "speed")
+                        .and_then(|speed| speed.trim().parse::<f32>().ok())
+                        .unwrap_or(1f32);
+                    velocity.0 = Point::new(hspeed * terrain_speed, vspeed * terrain_speed);
                 }
             }
         }
@@ -0,0 +1,325 @@
+//! Lays out styled text for drawing. The text is split into grapheme clusters, which are measured
+//! in their own fonts and wrapped into lines at the spaces between words. Everything that counts
+//! through text (the typewriter, pauses, pages) counts these glyphs, so that a character made of
+//! several bytes or code points is still only one step.
+//!
+//! Measuring text is slow, so a `TextLayoutCache` keeps the layouts of the texts that have been
+//! drawn recently, by their content and width.
+
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use game_engine::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::font::default::REGULAR_20 as DEFAULT_FONT;
+use crate::model::pretty_string::{Attributes, PrettyString};
+
+/// How many layouts a cache keeps before it forgets the least recently used one. This is enough
+/// for a screen full of the backlog.
+const CACHE_SIZE: usize = 32;
+
+/// The font to draw text with the attributes in.
+pub fn font(attributes: &Attributes) -> Font {
+    attributes.font().cloned().unwrap_or(DEFAULT_FONT)
+}
+
+fn same_font(a: &Attributes, b: &Attributes) -> bool {
+    (a.family, a.style, a.size) == (b.family, b.style, b.size)
+}
+
+fn is_line_break(grapheme: &str) -> bool {
+    grapheme == "\n" || grapheme == "\r\n"
+}
+
+fn is_space(grapheme: &str) -> bool {
+    !is_line_break(grapheme) && grapheme.chars().all(char::is_whitespace)
+}
+
+/// One grapheme cluster of the text. A line break is kept as a glyph with no text, so that the
+/// glyphs still line up with the text they came from.
+#[derive(Clone, Debug)]
+pub struct Glyph {
+    pub text: String,
+    pub attributes: Attributes,
+    /// The top left corner of the glyph, relative to the top left of the text.
+    pub position: Point,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub glyphs: Range<usize>,
+    /// The top of the line, relative to the top of the text.
+    pub y: i32,
+    pub spacing: i32,
+}
+
+/// Glyphs that are next to each other on a line and have the same attributes, which can be drawn
+/// all at once.
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub text: String,
+    pub attributes: Attributes,
+    pub position: Point,
+    pub width: u32,
+    pub height: u32,
+    pub glyphs: Range<usize>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct TextLayout {
+    glyphs: Vec<Glyph>,
+    lines: Vec<Line>,
+}
+
+/// The measurements of a glyph that are only needed until its line is finished.
+struct Metrics {
+    ascent: i32,
+    spacing: i32,
+}
+
+impl TextLayout {
+    /// Lays out the text, wrapping it to fit in the `max_width`. A word that is too long to fit on
+    /// a line of its own is broken between glyphs.
+    pub fn new(canvas: &mut dyn Canvas, text: &PrettyString, max_width: u32) -> game_engine::Result<Self> {
+        let graphemes: Vec<(&str, &Attributes)> = text.0
+            .iter()
+            .flat_map(|(text, attributes)| text.graphemes(true).map(move |grapheme| (grapheme, attributes)))
+            .collect();
+
+        canvas.set_font(DEFAULT_FONT);
+        let default_spacing = canvas.line_spacing()?;
+
+        let mut layout = TextLayout::default();
+        let mut metrics = vec![];
+        let mut x = 0;
+        let mut y = 0;
+        let mut start = 0;
+        while start < graphemes.len() {
+            // a word runs up to the end of the spaces after it, and a line break is a word of its own
+            let mut end = start;
+            while end < graphemes.len() && !is_space(graphemes[end].0) && !is_line_break(graphemes[end].0) {
+                end += 1;
+            }
+            while end < graphemes.len() && is_space(graphemes[end].0) {
+                end += 1;
+            }
+            if end == start {
+                end += 1;
+            }
+
+            let word = measure_word(canvas, &graphemes[start..end])?;
+            let word_width: i32 = word
+                .iter()
+                .zip(&graphemes[start..end])
+                .filter(|(_, (grapheme, _))| !is_space(grapheme))
+                .map(|((glyph, _), _)| glyph.width as i32)
+                .sum();
+            let fits_on_a_line = word_width <= max_width as i32;
+            if x != 0 && fits_on_a_line && x + word_width > max_width as i32 {
+                y = layout.finish_line(&mut metrics, y, default_spacing);
+                x = 0;
+            }
+            for (mut glyph, glyph_metrics) in word {
+                if x != 0 && !fits_on_a_line && x + glyph.width as i32 > max_width as i32 && !is_space(&glyph.text) {
+                    y = layout.finish_line(&mut metrics, y, default_spacing);
+                    x = 0;
+                }
+                glyph.position = Point::new(x, 0);
+                x += glyph.width as i32;
+                let line_break = is_line_break(&glyph.text);
+                if line_break {
+                    glyph.text.clear();
+                }
+                layout.glyphs.push(glyph);
+                metrics.push(glyph_metrics);
+                if line_break {
+                    y = layout.finish_line(&mut metrics, y, default_spacing);
+                    x = 0;
+                }
+            }
+            start = end;
+        }
+        if !metrics.is_empty() || layout.lines.is_empty() {
+            layout.finish_line(&mut metrics, y, default_spacing);
+        }
+        Ok(layout)
+    }
+
+    /// Ends the line that holds the glyphs since the last line, lining them up on one baseline,
+    /// and returns where the next line starts.
+    fn finish_line(&mut self, metrics: &mut Vec<Metrics>, y: i32, default_spacing: i32) -> i32 {
+        let start = self.lines.last().map(|line| line.glyphs.end).unwrap_or(0);
+        let ascent = metrics.iter().map(|metrics| metrics.ascent).max().unwrap_or(0);
+        let spacing = metrics.iter().map(|metrics| metrics.spacing).max().unwrap_or(default_spacing);
+        for (glyph, metrics) in self.glyphs[start..].iter_mut().zip(metrics.iter()) {
+            glyph.position.y = y + ascent - metrics.ascent;
+        }
+        metrics.clear();
+        self.lines.push(Line { glyphs: start..self.glyphs.len(), y, spacing });
+        y + spacing
+    }
+
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The number of glyphs.
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// The width of the widest line.
+    pub fn width(&self) -> u32 {
+        self.glyphs
+            .iter()
+            .filter(|glyph| !is_space(&glyph.text))
+            .map(|glyph| (glyph.position.x + glyph.width as i32) as u32)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The height of all the lines together.
+    pub fn height(&self) -> u32 {
+        self.lines
+            .last()
+            .map(|line| (line.y + line.spacing) as u32)
+            .unwrap_or(0)
+    }
+
+    /// Splits the lines into pages that each fit in the `height`, as the range of glyphs on each.
+    pub fn pages(&self, height: i32) -> Vec<Range<usize>> {
+        if self.lines.is_empty() {
+            return vec![0..0];
+        }
+        let mut pages = vec![];
+        let mut first = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > first && line.y + line.spacing - self.lines[first].y > height {
+                pages.push(self.lines[first].glyphs.start..self.lines[i - 1].glyphs.end);
+                first = i;
+            }
+        }
+        pages.push(self.lines[first].glyphs.start..self.glyphs.len());
+        pages
+    }
+
+    /// The top of the line that the glyph is on.
+    pub fn line_y(&self, glyph: usize) -> i32 {
+        self.lines
+            .iter()
+            .find(|line| line.glyphs.end > glyph)
+            .or(self.lines.last())
+            .map(|line| line.y)
+            .unwrap_or(0)
+    }
+
+    /// The runs of the glyphs in the range, split wherever the line or the attributes change.
+    /// Only the first `revealed` glyphs of the range are included, if it is given, so that text can
+    /// be shown a bit at a time.
+    pub fn runs(&self, glyphs: Range<usize>, revealed: Option<usize>) -> Vec<Run> {
+        let end = match revealed {
+            Some(revealed) => usize::min(glyphs.end, glyphs.start + revealed),
+            None => glyphs.end,
+        };
+        let mut runs: Vec<Run> = vec![];
+        for line in &self.lines {
+            let start = usize::max(line.glyphs.start, glyphs.start);
+            let line_end = usize::min(line.glyphs.end, end);
+            let mut continues = false;
+            for index in start..line_end {
+                let glyph = &self.glyphs[index];
+                if glyph.text.is_empty() {
+                    continues = false;
+                    continue;
+                }
+                match runs.last_mut() {
+                    Some(run) if continues && run.attributes == glyph.attributes => {
+                        run.text.push_str(&glyph.text);
+                        run.width = (glyph.position.x + glyph.width as i32 - run.position.x) as u32;
+                        run.height = u32::max(run.height, glyph.height);
+                        run.glyphs.end = index + 1;
+                    }
+                    _ => runs.push(Run {
+                        text: glyph.text.clone(),
+                        attributes: glyph.attributes,
+                        position: glyph.position,
+                        width: glyph.width,
+                        height: glyph.height,
+                        glyphs: index..index + 1,
+                    }),
+                }
+                continues = true;
+            }
+        }
+        runs
+    }
+}
+
+/// Measures each glyph of a word. Glyphs in the same font are measured together, so that the
+/// spacing between them matches how they are drawn.
+fn measure_word(canvas: &mut dyn Canvas, graphemes: &[(&str, &Attributes)]) -> game_engine::Result<Vec<(Glyph, Metrics)>> {
+    let mut glyphs = vec![];
+    let mut run = String::new();
+    let mut run_width = 0;
+    let mut previous: Option<&Attributes> = None;
+    for &(grapheme, attributes) in graphemes {
+        if previous.map(|previous| !same_font(previous, attributes)).unwrap_or(true) {
+            canvas.set_font(font(attributes));
+            run.clear();
+            run_width = 0;
+        }
+        previous = Some(attributes);
+        let ascent = canvas.font_ascent()?;
+        let spacing = canvas.line_spacing()?;
+        let (width, height) = if is_line_break(grapheme) {
+            (0, 0)
+        } else {
+            run.push_str(grapheme);
+            let Dimen { width, height } = canvas.measure_text(run.clone())?;
+            let advance = width.saturating_sub(run_width);
+            run_width = width;
+            (advance, height)
+        };
+        glyphs.push((
+            Glyph {
+                text: grapheme.to_owned(),
+                attributes: *attributes,
+                position: Point::default(),
+                width,
+                height,
+            },
+            Metrics { ascent, spacing },
+        ));
+    }
+    Ok(glyphs)
+}
+
+/// The layouts of the texts that were drawn recently. This is shared by reference, so that a
+/// `Drawable` can keep one even though it is only borrowed while rendering.
+#[derive(Default, Debug)]
+pub struct TextLayoutCache(Mutex<Vec<(PrettyString, u32, Arc<TextLayout>)>>);
+
+impl TextLayoutCache {
+    /// The layout of the text at the width, which is only worked out if it is not in the cache.
+    pub fn layout(&self, canvas: &mut dyn Canvas, text: &PrettyString, max_width: u32) -> game_engine::Result<Arc<TextLayout>> {
+        let mut cache = self.0.lock().unwrap();
+        if let Some(index) = cache.iter().position(|(cached, width, _)| cached == text && *width == max_width) {
+            let entry = cache.remove(index);
+            let layout = entry.2.clone();
+            cache.push(entry);
+            return Ok(layout);
+        }
+        let layout = Arc::new(TextLayout::new(canvas, text, max_width)?);
+        if cache.len() >= CACHE_SIZE {
+            cache.remove(0);
+        }
+        cache.push((text.clone(), max_width, layout.clone()));
+        Ok(layout)
+    }
+}
@@ -1,6 +1,21 @@
 use lazy_static::lazy_static;
 use game_engine::prelude::*;
-use crate::entity::wall::Wall;
+use crate::entity::{
+    self,
+    wall::Wall,
+    door::Door,
+    state_pickup::StatePickup,
+};
+use crate::resource::{
+    state::{State, MainState},
+    tile_animations::TileAnimations,
+    tile_properties::TilePropertyLayers,
+};
+use crate::scene;
 use crate::tile_set;
+use crate::model::{
+    tile_animation::TileAnimation,
+    tile_properties::TilePropertyLayer,
+};
 
 include!(concat!(env!("OUT_DIR"), "/tile_grids.rs"));
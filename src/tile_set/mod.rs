@@ -1,4 +1,6 @@
 use game_engine::prelude::*;
 use crate::image;
+use crate::model::tile_animation::{TileAnimation, TileFrame};
+use crate::model::tile_properties::TileProperties;
 
 include!(concat!(env!("OUT_DIR"), "/tile_sets.rs"));